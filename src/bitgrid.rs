@@ -0,0 +1,337 @@
+use std::cell::OnceCell;
+
+use crate::*;
+
+/******************************************************************************
+ * Cells
+ *****************************************************************************/
+
+/// The cells of a universe, stored as their states packed 64 per word (see
+/// `BitGrid`) as long as they are all dead or alive.
+///
+/// The cells computed 64 at a time (see `Universe::packed_step`) are only
+/// stored packed. They are unpacked to one byte per cell when they are read
+/// as such, e.g. by `Universe::cells`, and stored as bytes from the moment
+/// they are changed, i.e. borrowed mutably, until the next generation packs
+/// them again. The birth and survival masks of the last rule are kept too.
+#[derive(Default)]
+pub(crate) struct Cells {
+    bytes: OnceCell<Vec<Cell>>,
+    // `None` once packing failed, cells in other states than dead and alive
+    // being stored as bytes only
+    packed: OnceCell<Option<Packed>>,
+    masks: Option<(LifeLikeRule, Option<(u16, u16)>)>,
+}
+
+/// Cells packed 64 per word, rows of `width` cells starting at a new word.
+struct Packed {
+    width: usize,
+    words: Vec<u64>,
+}
+
+impl Cells {
+    /// Get the states of the cells packed 64 per word, packing them with the
+    /// given width if they are stored as bytes, or `None` if some cells are
+    /// neither dead nor alive.
+    pub(crate) fn packed(&self, width: usize) -> Option<&[u64]> {
+        self.packed
+            .get_or_init(|| {
+                let words = pack(self.bytes.get()?, width)?;
+                Some(Packed { width, words })
+            })
+            .as_ref()
+            .filter(|packed| packed.width == width)
+            .map(|packed| &packed.words[..])
+    }
+
+    /// Take the states of the cells packed 64 per word (see `packed`).
+    fn take_packed(&mut self, width: usize) -> Option<Vec<u64>> {
+        self.packed(width)?;
+        self.packed.take().flatten().map(|packed| packed.words)
+    }
+}
+
+impl From<Vec<Cell>> for Cells {
+    fn from(cells: Vec<Cell>) -> Self {
+        Cells { bytes: OnceCell::from(cells), ..Default::default() }
+    }
+}
+
+impl From<Cells> for Vec<Cell> {
+    fn from(mut cells: Cells) -> Self {
+        std::mem::take(&mut *cells)
+    }
+}
+
+impl std::ops::Deref for Cells {
+    type Target = Vec<Cell>;
+
+    fn deref(&self) -> &Vec<Cell> {
+        self.bytes.get_or_init(|| match self.packed.get() {
+            Some(Some(packed)) => unpack(&packed.words, packed.width),
+            _ => Vec::new(),
+        })
+    }
+}
+
+impl std::ops::DerefMut for Cells {
+    fn deref_mut(&mut self) -> &mut Vec<Cell> {
+        let bytes = match self.bytes.take() {
+            Some(bytes) => bytes,
+            None => (**self).clone(),
+        };
+        self.bytes = OnceCell::from(bytes);
+        self.packed = OnceCell::new();
+        self.bytes.get_mut().expect("cells are stored as bytes")
+    }
+}
+
+/// Pack the states of cells, relative to the background, or return `None` if
+/// some cells are neither dead nor alive.
+fn pack(cells: &[Cell], width: usize) -> Option<Vec<u64>> {
+    let mut words = Vec::with_capacity(cells.len().div_ceil(64));
+
+    for row in cells.chunks(width) {
+        for cells in row.chunks(64) {
+            let mut word = 0;
+            for (bit, cell) in cells.iter().enumerate() {
+                if cell.state() > 1 {
+                    return None;
+                }
+                word |= (cell.state() as u64) << bit;
+            }
+            words.push(word);
+        }
+    }
+
+    Some(words)
+}
+
+/// Unpack the states of cells.
+fn unpack(words: &[u64], width: usize) -> Vec<Cell> {
+    let stride = width.div_ceil(64);
+    let mut cells = Vec::with_capacity(width*(words.len()/stride));
+
+    for row in words.chunks(stride) {
+        for (k, &word) in row.iter().enumerate() {
+            let bits = (width - 64*k).min(64);
+            cells.extend((0..bits).map(|bit| Cell::from(((word >> bit) & 1) as u8)));
+        }
+    }
+
+    cells
+}
+
+impl Universe {
+    /// Compute the next generation 64 cells at a time (see `BitGrid`),
+    /// returning whether it did.
+    ///
+    /// It does if the rule is an outer totalistic 2 states rule on the Moore
+    /// neighbourhood, or an alternating rule whose current rule is, and if
+    /// the cells of the universe can be packed.
+    pub(crate) fn packed_step(&mut self) -> bool {
+        let rule = match &self.rule {
+            Rule::LifeLike(rule) => *rule,
+            Rule::Alternating(rule) => match rule.current(self) {
+                Rule::LifeLike(rule) => *rule,
+                _ => return false,
+            },
+            _ => return false,
+        };
+        if rule.states() != 2 {
+            return false;
+        }
+
+        let background = self.background;
+        let (wrap, edge) = match self.topology {
+            Topology::Torus { col_shift: 0, row_shift: 0 } => (true, 0),
+            // cells beyond the edges of a plane are dead, and cells beyond
+            // the frame of an unbounded universe are in the background state
+            Topology::Plane => (false, 0),
+            Topology::Unbounded if background == Cell::Alive => (false, !0),
+            Topology::Unbounded => (false, 0),
+            _ => return false,
+        };
+
+        let masks = match self.cells.masks {
+            Some((masks_rule, masks)) if masks_rule == rule => masks,
+            _ => rule.moore_counts(),
+        };
+        self.cells.masks = Some((rule, masks));
+        let (birth, survival) = match masks {
+            Some(masks) => masks,
+            None => return false,
+        };
+
+        let width = self.width as usize;
+        let words = match self.cells.take_packed(width) {
+            Some(words) => words,
+            None => return false,
+        };
+
+        let mut grid = BitGrid {
+            width: self.width,
+            height: self.height,
+            stride: width.div_ceil(64),
+            words,
+            wrap,
+            edge,
+        };
+        // cells are computed with their actual state, and stored relative to
+        // the next background
+        if background == Cell::Alive {
+            grid.flip();
+        }
+        let mut next = grid.next_generation(birth, survival);
+        let next_background = rule.next_background(background);
+        if next_background == Cell::Alive {
+            next.flip();
+        }
+
+        self.cells = Cells {
+            packed: OnceCell::from(Some(Packed { width, words: next.words })),
+            masks: Some((rule, Some((birth, survival)))),
+            ..Default::default()
+        };
+        self.background = next_background;
+        true
+    }
+}
+
+/******************************************************************************
+ * Bit grid
+ *****************************************************************************/
+
+/// The cells of a universe packed 64 per word, so that the next generation of
+/// outer totalistic 2 states rules on the Moore neighbourhood is computed 64
+/// cells at a time with bitwise operations.
+///
+/// Each row starts at a new word, the cell at column `col` being the bit
+/// `col%64` of the word `col/64` of its row. The bits beyond the last column
+/// of a row are always unset.
+struct BitGrid {
+    width: i32,
+    height: i32,
+    stride: usize,
+    words: Vec<u64>,
+    // whether the rows and the columns wrap around, or the state of the cells
+    // beyond the edges, all bits set for live cells
+    wrap: bool,
+    edge: u64,
+}
+
+/// Add 3 bits planes, giving the planes of the sum and of the carry.
+fn full_add(a: u64, b: u64, c: u64) -> (u64, u64) {
+    (a ^ b ^ c, (a & b) | (c & (a ^ b)))
+}
+
+impl BitGrid {
+    /// Toggle all the cells.
+    fn flip(&mut self) {
+        let last_mask = self.last_mask();
+        for row in self.words.chunks_mut(self.stride) {
+            row.iter_mut().for_each(|word| *word = !*word);
+            row[self.stride - 1] &= last_mask;
+        }
+    }
+
+    /// Get the mask of the cells of the last word of a row.
+    fn last_mask(&self) -> u64 {
+        match self.width%64 {
+            0 => !0,
+            bits => (1 << bits) - 1,
+        }
+    }
+
+    /// Get the words of the row `row`, following the edges of the grid.
+    fn row<'a>(&'a self, row: i32, edge_row: &'a [u64]) -> &'a [u64] {
+        let row = match row {
+            row if (0..self.height).contains(&row) => row,
+            _ if !self.wrap => return edge_row,
+            row => row.rem_euclid(self.height),
+        } as usize;
+
+        &self.words[row*self.stride..(row + 1)*self.stride]
+    }
+
+    /// Get the west and the east neighbours of the cells of the word `k` of a
+    /// row.
+    fn neighbours(&self, words: &[u64], k: usize) -> (u64, u64) {
+        let last = self.stride - 1;
+        let last_bit = ((self.width - 1)%64) as u32;
+
+        let west = match k {
+            0 if self.wrap => (words[last] >> last_bit) & 1,
+            0 => self.edge & 1,
+            k => words[k - 1] >> 63,
+        };
+        let east = match k {
+            k if k < last => words[k + 1] << 63,
+            _ if self.wrap => (words[0] & 1) << last_bit,
+            _ => (self.edge & 1) << last_bit,
+        };
+
+        ((words[k] << 1) | west, (words[k] >> 1) | east)
+    }
+
+    /// Compute the next generation of an outer totalistic rule, given its
+    /// birth and survival neighbour counts as bit masks (bit N being set when
+    /// a cell with N live neighbours is born or survives).
+    fn next_generation(&self, birth: u16, survival: u16) -> BitGrid {
+        let last_mask = self.last_mask();
+        let mut edge_row = vec![self.edge; self.stride];
+        edge_row[self.stride - 1] &= last_mask;
+
+        let mut words = vec![0; self.words.len()];
+
+        for row in 0..self.height {
+            let above = self.row(row - 1, &edge_row);
+            let current = self.row(row, &edge_row);
+            let below = self.row(row + 1, &edge_row);
+
+            for k in 0..self.stride {
+                let (above_west, above_east) = self.neighbours(above, k);
+                let (west, east) = self.neighbours(current, k);
+                let (below_west, below_east) = self.neighbours(below, k);
+
+                // count the 8 neighbours of the 64 cells at once, in 4 bits
+                // planes
+                let (above_0, above_1) = full_add(above_west, above[k], above_east);
+                let (current_0, current_1) = (west ^ east, west & east);
+                let (below_0, below_1) = full_add(below_west, below[k], below_east);
+
+                let (bit_0, carry_0) = full_add(above_0, current_0, below_0);
+                let (sum_1, carry_1) = full_add(above_1, current_1, below_1);
+                let bit_1 = sum_1 ^ carry_0;
+                let carry_2 = sum_1 & carry_0;
+                let bit_2 = carry_1 ^ carry_2;
+                let bit_3 = carry_1 & carry_2;
+
+                let planes = [bit_0, bit_1, bit_2, bit_3];
+                let (born, survive) = (0..=8).fold((0, 0), |(born, survive), count| {
+                    let matches = planes
+                        .iter()
+                        .enumerate()
+                        .fold(!0, |matches, (bit, &plane)| {
+                            matches & if count & (1 << bit) != 0 { plane } else { !plane }
+                        });
+
+                    (
+                        born | if birth & (1 << count) != 0 { matches } else { 0 },
+                        survive | if survival & (1 << count) != 0 { matches } else { 0 },
+                    )
+                });
+
+                let alive = current[k];
+                let next = (!alive & born) | (alive & survive);
+
+                words[row as usize*self.stride + k] = match k {
+                    k if k == self.stride - 1 => next & last_mask,
+                    _ => next,
+                };
+            }
+        }
+
+        BitGrid { words, ..*self }
+    }
+}
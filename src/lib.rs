@@ -3,13 +3,31 @@ use wasm_bindgen::prelude::*;
 extern crate js_sys;
 extern crate web_sys;
 
+mod bitgrid;
 mod life_106_codec;
+mod random;
 mod rle_codec;
 mod render;
+mod resize;
+mod reversible;
+mod rule;
+mod schedule;
+mod topology;
+mod turmite;
+mod unbounded;
+mod zone;
+
+use bitgrid::Cells;
 
 pub use rle_codec::*;
 pub use life_106_codec::*;
-pub use render::*;
+pub use random::*;
+pub use resize::*;
+pub use rule::*;
+pub use schedule::*;
+pub use topology::*;
+pub use turmite::*;
+pub use zone::*;
 
 // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global
 // allocator.
@@ -17,28 +35,74 @@ pub use render::*;
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
-#[wasm_bindgen]
-#[repr(u8)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum Cell {
-    Dead = 0,
-    Alive = 1,
+/// The state of a cell.
+///
+/// State 0 is the dead state and state 1 the alive state. Multi-state rules
+/// (e.g. Generations) use the states above 1.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Cell(u8);
+
+// Named like the variants of the former `Cell` enum.
+#[allow(non_upper_case_globals)]
+impl Cell {
+    pub const Dead: Cell = Cell(0);
+    pub const Alive: Cell = Cell(1);
 }
 
 impl Cell {
+    /// Get the state index of the cell.
+    pub fn state(&self) -> u8 {
+        self.0
+    }
+
+    pub fn is_alive(&self) -> bool {
+        *self == Cell::Alive
+    }
+
+    /// Toggle the cell between the alive and the dead state.
+    ///
+    /// Cells in any other state become dead.
     pub fn toggle(&mut self) {
         *self = match *self {
-            Cell::Alive => Cell::Dead,
             Cell::Dead => Cell::Alive,
+            _ => Cell::Dead,
         }
     }
 }
 
+impl From<u8> for Cell {
+    fn from(state: u8) -> Self {
+        Cell(state)
+    }
+}
+
+impl From<Cell> for u8 {
+    fn from(cell: Cell) -> Self {
+        cell.0
+    }
+}
+
 #[wasm_bindgen]
 pub struct Universe {
     width: i32,
     height: i32,
-    cells: Vec<Cell>,
+    cells: Cells,
+    previous: Option<Vec<Cell>>,
+    rule: Rule,
+    generation: u32,
+    fixed_boundary: bool,
+    topology: Topology,
+    origin: (i32, i32),
+    viewport: Option<(i32, i32, i32, i32)>,
+    background: Cell,
+    random: Random,
+    schedule: Schedule,
+    turmites: Vec<Turmite>,
+    turmite_rule: TurmiteRule,
+    turmites_only: bool,
+    zones: Option<Vec<u8>>,
+    zone_rules: Vec<Option<Rule>>,
 }
 
 fn coordinates_to_idx(
@@ -76,6 +140,80 @@ impl Universe {
             .unwrap()
     }
 
+    /// Get a copy of cells of the size of the universe translated by (`dx`,
+    /// `dy`), cells translated beyond the edges of a bounded universe being
+    /// dropped.
+    fn translated(&self, cells: &[Cell], dx: i32, dy: i32) -> Vec<Cell> {
+        let mut new_cells = vec![Cell::Dead; (self.width*self.height) as usize];
+        cells
+            .iter()
+            .enumerate()
+            .filter(|(_, &cell)| cell != Cell::Dead)
+            .for_each(|(idx, &cell)| {
+                let (x, y) = self.get_coordinates(idx);
+                if let Some(new_idx) = self.neighbour_index(x + dx, y + dy) {
+                    new_cells[new_idx] = cell;
+                }
+            });
+        new_cells
+    }
+
+    /// Get a copy of values of the cells of the universe, e.g. its cells or
+    /// its zones, framed to the rectangle of `width` by `height` cells whose
+    /// top left cell is at (`left`, `top`), values out of the universe being
+    /// `fill`.
+    fn reframed<T: Copy>(
+        &self,
+        values: &[T],
+        fill: T,
+        (left, top, width, height): (i32, i32, i32, i32),
+    ) -> Vec<T> {
+        let mut new_values = vec![fill; (width*height) as usize];
+
+        for row in 0.max(top)..self.height.min(top + height) {
+            for col in 0.max(left)..self.width.min(left + width) {
+                let new_idx = ((row - top)*width + col - left) as usize;
+                new_values[new_idx] = values[(row*self.width + col) as usize];
+            }
+        }
+
+        new_values
+    }
+
+    /// Move the frame of the universe to the rectangle of `width` by `height`
+    /// cells whose top left cell is at (`left`, `top`), keeping the cells,
+    /// zones and turmites in both frames, new cells being dead.
+    ///
+    /// The origin of an unbounded universe follows its frame, so that its
+    /// cells keep their coordinates (see `Universe::origin`).
+    fn reframe(&mut self, left: i32, top: i32, width: i32, height: i32) {
+        let frame = (left, top, width, height);
+
+        *self.cells = self.reframed(&self.cells, Cell::Dead, frame);
+        if let Some(previous) = self.previous.as_ref() {
+            self.previous = Some(self.reframed(previous, Cell::Dead, frame));
+        }
+        if let Some(zones) = self.zones.as_ref() {
+            self.zones = Some(self.reframed(zones, 0, frame));
+        }
+        self.reframe_turmites(frame);
+
+        self.width = width;
+        self.height = height;
+        if self.topology == Topology::Unbounded {
+            self.origin = (self.origin.0 + left, self.origin.1 + top);
+        }
+    }
+
+    /// Set the cells to their actual state if the background of the universe
+    /// is alive, the background becoming dead (see `Universe::background`).
+    fn clear_background(&mut self) {
+        if self.background.is_alive() {
+            self.cells.iter_mut().for_each(Cell::toggle);
+            self.background = Cell::Dead;
+        }
+    }
+
     fn get_coordinates(&self, index: usize) -> (i32, i32) {
         let index = index%self.cells.len();
         idx_to_coordinates(index, self.width as u32, self.height as u32)
@@ -83,19 +221,20 @@ impl Universe {
             .unwrap()
     }
 
-    fn live_neighbour_count(&self, col: i32, row: i32) -> u8 {
-        let mut count = 0;
+    /// Get the neighbourhood index of a cell, that is its 3x3 neighbourhood
+    /// live cells packed in a 9 bits value (see `NEIGHBOURHOOD_CENTER`).
+    fn neighbourhood(&self, col: i32, row: i32) -> u16 {
+        let mut neighbourhood = 0;
 
         for delta_row in [-1, 0, 1].iter().cloned() {
             for delta_col in [-1, 0, 1].iter().cloned() {
-                if delta_row != 0 || delta_col != 0 {
-                    let idx = self.get_index(col + delta_col, row + delta_row);
-                    count += self.cells[idx] as u8;
+                if self.neighbour(col + delta_col, row + delta_row).is_alive() {
+                    neighbourhood |= neighbourhood_bit(delta_col, delta_row);
                 }
             }
         }
 
-        count
+        neighbourhood
     }
 }
 
@@ -118,20 +257,42 @@ impl Universe {
         Universe {
             width,
             height,
-            cells,
+            cells: cells.into(),
+            previous: None,
+            rule: Rule::default(),
+            generation: 0,
+            fixed_boundary: false,
+            topology: Topology::default(),
+            origin: (0, 0),
+            viewport: None,
+            background: Cell::Dead,
+            random: Random::from_entropy(),
+            schedule: Schedule::default(),
+            turmites: Vec::new(),
+            turmite_rule: TurmiteRule::default(),
+            turmites_only: false,
+            zones: None,
+            zone_rules: Vec::new(),
         }
     }
 
     /// Set all cells to the dead state.
+    ///
+    /// In reversible mode, the cells of the previous generation are cleared
+    /// too.
     pub fn clear(&mut self) {
-        self.cells = (0..self.width*self.height).map(|_| Cell::Dead).collect();
+        *self.cells = (0..self.width*self.height).map(|_| Cell::Dead).collect();
+        self.clear_previous();
+        self.generation = 0;
+        self.background = Cell::Dead;
     }
 
-    /// Randomly set cells to be alive or dead.
+    /// Randomly set cells to be alive or dead, using the random number
+    /// generator of the universe (see `set_seed`).
     pub fn randomize(&mut self) {
-        self.cells = (0..self.width*self.height)
+        *self.cells = (0..self.width*self.height)
             .map(|_| {
-                if js_sys::Math::random() < 0.5 {
+                if self.random.next_f64() < 0.5 {
                     Cell::Alive
                 } else {
                     Cell::Dead
@@ -149,73 +310,127 @@ impl Universe {
     }
 
     /// Set the width of the universe.
-    /// 
-    /// Resets all cells to the dead state.
-    pub fn set_width(&mut self, new_width: i32) {
-        let new_width = 1.max(new_width) as u32;
-
-        let mut cells = vec![Cell::Dead; (new_width*self.height as u32) as usize];
-
-        for y in 0..(self.height as u32) {
-            for x in 0..new_width.min(self.width as u32) {
-                let new_idx = coordinates_to_idx(x, y, new_width as u32, self.height as u32).unwrap();
-                let idx = coordinates_to_idx(x, y, self.width as u32, self.height as u32).unwrap();
-                cells[new_idx] = self.cells[idx];
-            }
+    ///
+    /// Cells keep their coordinates: columns beyond the new width are removed,
+    /// and new columns are dead. In reversible mode, the cells of the previous
+    /// generation are cleared.
+    ///
+    /// Margolus rules need an even width (see `set_rule`).
+    pub fn set_width(&mut self, new_width: i32) -> Result<(), ResizeError> {
+        let new_width = 1.max(new_width);
+        if !self.rule.fits(new_width, self.height) {
+            return Err(ResizeError::InvalidSize);
         }
 
-        self.cells = cells;
-        self.width = new_width as i32;
+        self.reframe(0, 0, new_width, self.height);
+        self.clear_previous();
+        Ok(())
     }
 
     /// Set the height of the universe.
-    /// 
-    /// Resets all cells to the dead state.
-    pub fn set_height(&mut self, new_height: i32) {
-        let new_height = 1.max(new_height) as u32;
-
-        let mut cells = vec![Cell::Dead; (self.width as u32*new_height) as usize];
-
-        for y in 0..new_height.min(self.height as u32) {
-            for x in 0..(self.width as u32) {
-                let new_idx = coordinates_to_idx(x, y, self.width as u32, new_height as u32).unwrap();
-                let idx = coordinates_to_idx(x, y, self.width as u32, self.height as u32).unwrap();
-                cells[new_idx] = self.cells[idx];
-            }
+    ///
+    /// Cells keep their coordinates: rows beyond the new height are removed,
+    /// and new rows are dead. In reversible mode, the cells of the previous
+    /// generation are cleared.
+    ///
+    /// Margolus rules need an even height (see `set_rule`).
+    pub fn set_height(&mut self, new_height: i32) -> Result<(), ResizeError> {
+        let new_height = 1.max(new_height);
+        if !self.rule.fits(self.width, new_height) {
+            return Err(ResizeError::InvalidSize);
         }
 
-        self.cells = cells;
-        self.height = new_height as i32;
+        self.reframe(0, 0, self.width, new_height);
+        self.clear_previous();
+        Ok(())
     }
 
+    /// Get a pointer to the cells of the universe.
+    ///
+    /// Each cell is stored as one byte holding its state index, the cells
+    /// computed 64 at a time being unpacked to bytes on the first call.
     pub fn cells(&self) -> *const Cell {
         self.cells.as_ptr()
     }
 
+    /// Get a pointer to the cells of the universe packed 64 per word, or a
+    /// null pointer if some cells are neither dead nor alive.
+    ///
+    /// Each row starts at a new word, the cell at column `col` being the bit
+    /// `col%64` of the word `col/64` of its row. As in `cells`, a set bit
+    /// means that the state of the cell differs from the background.
+    pub fn packed_cells(&self) -> *const u64 {
+        match self.cells.packed(self.width as usize) {
+            Some(words) => words.as_ptr(),
+            None => std::ptr::null(),
+        }
+    }
+
+    /// Get the number of words pointed to by `packed_cells`, or 0 if the
+    /// cells cannot be packed.
+    pub fn packed_len(&self) -> usize {
+        self.cells.packed(self.width as usize).map_or(0, <[u64]>::len)
+    }
+
+    /// Translate the cells and the turmites of the universe by (`dx`, `dy`).
+    ///
+    /// The origin of an unbounded universe is translated instead (see
+    /// `origin`).
     pub fn translate(&mut self, dx: i32, dy: i32) {
-        let mut new_cells = vec![Cell::Dead; (self.width*self.height) as usize];
-        self.cells
-            .iter()
-            .enumerate()
-            .filter(|(_, &cell)| cell == Cell::Alive)
-            .for_each(|(idx, &cell)| {
-                let (x, y) = self.get_coordinates(idx);
-                let new_idx = self.get_index(x + dx, y + dy);
-                new_cells[new_idx] = cell;
-            });
-        self.cells = new_cells;
+        if self.topology == Topology::Unbounded {
+            self.origin = (self.origin.0 + dx, self.origin.1 + dy);
+            return;
+        }
+
+        *self.cells = self.translated(&self.cells, dx, dy);
+        if let Some(previous) = &self.previous {
+            self.previous = Some(self.translated(previous, dx, dy));
+        }
+        self.translate_turmites(dx, dy);
     }
 
-    /// Get the state of a cell in the universe.
-    pub fn get_cell(&self, col: i32, row: i32) -> Cell {
-        let idx = self.get_index(col, row);
-        self.cells[idx]
+    /// Get the number of cells in each state, indexed by state.
+    ///
+    /// With species rules (e.g. "QuadLife"), this gives the population of
+    /// each species. Cells are counted with their actual state, whatever the
+    /// background of the universe (see `background`).
+    pub fn populations(&self) -> Vec<u32> {
+        let mut populations = vec![0; self.rule.states() as usize];
+
+        for cell in self.cells.iter() {
+            let state = cell.state() ^ self.background.state();
+            if let Some(population) = populations.get_mut(state as usize) {
+                *population += 1;
+            }
+        }
+
+        populations
     }
 
-    /// Set the state of a cell in the universe.
-    pub fn set_cell(&mut self, col: i32, row: i32, state: Cell) {
-        let idx = self.get_index(col, row);
-        self.cells[idx] = state;
+    /// Get the state of a cell in the universe (see `origin`).
+    ///
+    /// Coordinates wrap around on a torus, and are an error beyond the edges
+    /// of a bounded universe (see `set_topology`). Cells beyond the frame of
+    /// an unbounded universe are dead.
+    pub fn get_cell(&self, col: i32, row: i32) -> Result<u8, TopologyError> {
+        let (col, row) = (col - self.origin.0, row - self.origin.1);
+        if self.topology == Topology::Unbounded {
+            return Ok(self.neighbour(col, row).state());
+        }
+
+        let idx = self.checked_index(col, row)?;
+        Ok(self.cells[idx].state())
+    }
+
+    /// Set the state of a cell in the universe (see `origin`).
+    ///
+    /// Coordinates wrap around on a torus, and are an error beyond the edges
+    /// of a bounded universe (see `set_topology`). The frame of an unbounded
+    /// universe grows to include the cell.
+    pub fn set_cell(&mut self, col: i32, row: i32, state: u8) -> Result<(), TopologyError> {
+        let idx = self.cell_index_mut(col, row)?;
+        self.cells[idx] = Cell::from(state);
+        Ok(())
     }
 
     /// Set the state of a list of cells in the universe.
@@ -224,64 +439,91 @@ impl Universe {
     pub fn set_cells(
         &mut self,
         cells: js_sys::Array,
-        state: Cell,
+        state: u8,
     ) -> Result<(), JsError> {
         cells
             .iter()
-            .map(|value| {
+            .try_for_each(|value| {
                 if value.is_array() {
                     let cell = value.unchecked_into::<js_sys::Array>();
                     let (col, row) = js_array_to_coordinate_tuple(&cell)?;
-                    self.set_cell(col, row, state);
-                    Ok(())
+                    self.set_cell(col, row, state)
+                        .map_err(|err| JsError::new(&err.to_string()))
                 } else { Err(JsError::new("Invalid type")) }
             })
-            .collect::<Result<(), JsError>>()
     }
 
-    /// Set the state of a cell in the universe.
-    pub fn toggle_cell(&mut self, col: i32, row: i32) {
-        let idx = self.get_index(col, row);
+    /// Toggle a cell of the universe between the alive and the dead state,
+    /// cells in any other state becoming dead (see `set_cell` for the
+    /// coordinates).
+    pub fn toggle_cell(&mut self, col: i32, row: i32) -> Result<(), TopologyError> {
+        let idx = self.cell_index_mut(col, row)?;
         self.cells[idx].toggle();
+        Ok(())
     }
 
-    /// Toggle the state of a cell in the universe.
+    /// Toggle a list of cells of the universe (see `toggle_cell`).
     /// 
     /// Expects an array of arrays of the form [[row, col], [row, col], ...]
     pub fn toggle_cells(&mut self, cells: js_sys::Array)
         -> Result<(), JsError> {
         cells
             .iter()
-            .map(|value| {
+            .try_for_each(|value| {
                 let cell = value.unchecked_into::<js_sys::Array>();
                 let (col, row) = js_array_to_coordinate_tuple(&cell)?;
-                self.toggle_cell(col, row);
-                Ok(())
+                self.toggle_cell(col, row)
+                    .map_err(|err| JsError::new(&err.to_string()))
             })
-            .collect::<Result<(), JsError>>()
     }
 
+    /// Compute the next generation of the universe according to its rule.
+    ///
+    /// In reversible mode, see `set_reversible`, the next generation also
+    /// depends on the previous generation. Otherwise, cells are updated
+    /// according to the schedule of the universe (see `set_schedule`).
+    ///
+    /// The turmites of the universe then move (see `add_turmite`), unless
+    /// the universe only moves its turmites (see `set_turmites_only`).
+    ///
+    /// The frame of an unbounded universe first moves to follow its pattern
+    /// (see `origin`).
     pub fn tick(&mut self) {
-        let mut cells = self.cells.clone();
-
-        for row in 0..self.height {
-            for col in 0..self.width {
-                let idx = self.get_index(col, row);
-                let cell = self.cells[idx];
-                let live_neighbours = self.live_neighbour_count(col, row);
-
-                let next_cell = match (cell, live_neighbours) {
-                    (Cell::Alive, x) if x < 2 => Cell::Dead,
-                    (Cell::Alive, 2) | (Cell::Alive, 3) => Cell::Alive,
-                    (Cell::Alive, x) if x > 3 => Cell::Dead,
-                    (Cell::Dead, 3) => Cell::Alive,
-                    (otherwise, _) => otherwise,
-                };
-
-                cells[idx] = next_cell;
-            }
+        self.fit_frame();
+
+        if self.turmites_only {
+            // cells are only updated by the turmites
+        } else if self.previous.is_some() {
+            self.second_order_step();
+        } else if self.asynchronous_step() {
+            // cells are updated one at a time
+        } else if self.zones.is_some() {
+            *self.cells = self.zoned_generation();
+        } else if self.packed_step() {
+            // cells are updated 64 at a time
+        } else {
+            *self.cells = self.rule.next_generation(self);
+            self.background = self.rule.next_background(self);
         }
+        self.step_turmites();
+        self.generation += 1;
+    }
+
+    /// Get the state of the background of the universe.
+    ///
+    /// With rules containing B0, the whole universe turns on at every other
+    /// generation (or at every generation if the rule contains S8 too). Such
+    /// rules are emulated by storing the cells relative to the background: a
+    /// cell is stored alive when its state differs from the state of the
+    /// background. Cells are got, set and rendered relative to the background,
+    /// so that patterns do not strobe.
+    pub fn background(&self) -> u8 {
+        self.background.state()
+    }
 
-        self.cells = cells;
+    /// Get the number of generations computed since the universe was last
+    /// cleared.
+    pub fn generation(&self) -> u32 {
+        self.generation
     }
 }
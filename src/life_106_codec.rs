@@ -8,11 +8,15 @@ use crate::*;
 
 #[wasm_bindgen]
 impl Universe {
+/// Write the live cells of the universe in Life 1.06 format, with their
+/// actual state whatever the background of the universe (see `background`).
 pub fn to_life_106(&self) -> String {
+    let background = self.background.state();
+
     format!("#Life 1.06\n{}",
         self.cells.iter()
             .enumerate()
-            .filter(|(_, cell)| **cell == Cell::Alive)
+            .filter(|(_, cell)| cell.state() ^ background == 1)
             .map(|(idx, _)| idx_to_coordinates(idx, self.width as u32, self.height as u32).unwrap())
             .map(|(x, y)| format!("{} {}", x, y))
             .collect::<Vec<String>>()
@@ -93,8 +97,8 @@ pub fn from_life_106(value: JsValue) -> Result<Universe, Life106DecoderError> {
         universe.set_cell(
             x - x_min,
             y - y_min,
-            Cell::Alive,
-        );
+            Cell::Alive.into(),
+        ).ok();
     }
 
     Ok(universe)
@@ -0,0 +1,121 @@
+use wasm_bindgen::prelude::*;
+
+use crate::*;
+
+/******************************************************************************
+ * Random number generator
+ *****************************************************************************/
+
+/// A SplitMix64 pseudo-random number generator.
+///
+/// The state of the generator is a single 64 bits value, so that a run can be
+/// reproduced from a seed, and resumed from a saved state. Numbers can be
+/// drawn from a shared reference, so that rules can draw numbers while
+/// computing the next generation of a universe.
+#[derive(Debug, Default)]
+pub struct Random {
+    state: std::cell::Cell<u64>,
+}
+
+impl Random {
+    /// Create a generator with the given seed.
+    pub fn new(seed: u64) -> Self {
+        Random { state: std::cell::Cell::new(seed) }
+    }
+
+    /// Create a generator with a random seed, drawn with `Math.random` in the
+    /// browser, or from the clock elsewhere.
+    pub fn from_entropy() -> Self {
+        #[cfg(target_arch = "wasm32")]
+        let seed = {
+            let bits = || (js_sys::Math::random()*(1u64 << 32) as f64) as u64;
+            (bits() << 32) | bits()
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+
+        Random::new(seed)
+    }
+
+    /// Get the state of the generator.
+    pub fn state(&self) -> u64 {
+        self.state.get()
+    }
+
+    /// Set the state of the generator.
+    pub fn set_state(&self, state: u64) {
+        self.state.set(state);
+    }
+
+    /// Draw a number uniformly distributed over all 64 bits values.
+    pub fn next_u64(&self) -> u64 {
+        let state = self.state.get().wrapping_add(0x9e3779b97f4a7c15);
+        self.state.set(state);
+
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Draw a number uniformly distributed in [0, 1).
+    pub fn next_f64(&self) -> f64 {
+        (self.next_u64() >> 11) as f64/(1u64 << 53) as f64
+    }
+
+    /// Draw a number uniformly distributed in [0, `n`).
+    pub fn next_below(&self, n: u32) -> u32 {
+        (((self.next_u64() >> 32)*n as u64) >> 32) as u32
+    }
+}
+
+/******************************************************************************
+ * Universe
+ *****************************************************************************/
+
+pub enum RandomStateError {
+    InvalidState,
+}
+
+impl std::fmt::Display for RandomStateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RandomStateError::InvalidState => write!(f, "invalid random state"),
+        }
+    }
+}
+
+impl From<RandomStateError> for JsValue {
+    fn from(err: RandomStateError) -> Self {
+        JsValue::from_str(&err.to_string())
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+/// Seed the random number generator of the universe, used by `randomize` and
+/// by stochastic rules.
+///
+/// A new universe is seeded randomly, so that `randomize` gives a new
+/// pattern each time unless a seed is set.
+pub fn set_seed(&mut self, seed: u64) {
+    self.random.set_state(seed);
+}
+
+/// Get the state of the random number generator of the universe, as 16
+/// hexadecimal digits.
+pub fn random_state(&self) -> String {
+    format!("{:016x}", self.random.state())
+}
+
+/// Restore a state of the random number generator of the universe, as given
+/// by `random_state`.
+pub fn set_random_state(&mut self, state: &str) -> Result<(), RandomStateError> {
+    let state = u64::from_str_radix(state.trim(), 16)
+        .or(Err(RandomStateError::InvalidState))?;
+
+    self.random.set_state(state);
+    Ok(())
+}}
@@ -2,15 +2,142 @@ use wasm_bindgen::prelude::*;
 
 use crate::*;
 
+fn theme_color(theme: &JsValue, key: &str, default: &str) -> String {
+    js_sys::Reflect::get(theme, &key.into()).ok()
+        .and_then(|value| value.as_string())
+        .unwrap_or_else(|| String::from(default))
+}
+
+fn parse_hex_color(color: &str) -> Option<[u8; 3]> {
+    let hex = color.strip_prefix('#')?;
+    let channel = |i: usize, len: usize| {
+        let value = u8::from_str_radix(hex.get(i*len..(i + 1)*len)?, 16).ok()?;
+        Some(if len == 1 { value*17 } else { value })
+    };
+
+    match hex.len() {
+        3 => Some([channel(0, 1)?, channel(1, 1)?, channel(2, 1)?]),
+        6 => Some([channel(0, 2)?, channel(1, 2)?, channel(2, 2)?]),
+        _ => None,
+    }
+}
+
+/// Blend two colors, `t` going from 0 (`from` color) to 1 (`to` color).
+///
+/// Returns the `from` color if one of the colors is not an hexadecimal color.
+fn blend_colors(from: &str, to: &str, t: f64) -> String {
+    match (parse_hex_color(from), parse_hex_color(to)) {
+        (Some(from), Some(to)) => {
+            let channel = |i: usize| {
+                (from[i] as f64 + t*(to[i] as f64 - from[i] as f64)).round() as u8
+            };
+            format!("#{:02x}{:02x}{:02x}", channel(0), channel(1), channel(2))
+        },
+        _ => String::from(from),
+    }
+}
+
+/// Fill a pointy-top hexagon of width `size` centered on (`x`, `y`).
+fn fill_hexagon(
+    context: &web_sys::CanvasRenderingContext2d,
+    x: f64,
+    y: f64,
+    size: f64,
+) {
+    let half_width = size/2.0;
+    let radius = size/3f64.sqrt();
+
+    context.begin_path();
+    context.move_to(x, y - radius);
+    context.line_to(x + half_width, y - radius/2.0);
+    context.line_to(x + half_width, y + radius/2.0);
+    context.line_to(x, y + radius);
+    context.line_to(x - half_width, y + radius/2.0);
+    context.line_to(x - half_width, y - radius/2.0);
+    context.close_path();
+    context.fill();
+}
+
+/// Fill a triangle of width `size` centered on (`x`, `y`) pointing to the
+/// north (0), east (1), south (2) or west (3).
+fn fill_arrow(
+    context: &web_sys::CanvasRenderingContext2d,
+    x: f64,
+    y: f64,
+    size: f64,
+    heading: u8,
+) {
+    let half_width = size/2.0;
+    let rotate = |dx: f64, dy: f64| match heading {
+        0 => (x + dx, y + dy),
+        1 => (x - dy, y + dx),
+        2 => (x - dx, y - dy),
+        _ => (x + dy, y - dx),
+    };
+    let points = [
+        rotate(0.0, -half_width),
+        rotate(half_width, half_width),
+        rotate(-half_width, half_width),
+    ];
+
+    context.begin_path();
+    context.move_to(points[0].0, points[0].1);
+    context.line_to(points[1].0, points[1].1);
+    context.line_to(points[2].0, points[2].1);
+    context.close_path();
+    context.fill();
+}
+
+impl Universe {
+/// Compute the colors of every state of the rule of the universe.
+///
+/// The colors are read from the `palette` array of the theme, the missing
+/// ones defaulting to the colors defined by the rule if any, then to
+/// `deadCell` for state 0, `aliveCell` for state 1, and to a fade from
+/// `aliveCell` to `deadCell` for the other states.
+fn palette(&self, theme: &JsValue) -> Vec<String> {
+    let alive_color = theme_color(theme, "aliveCell", "#000000");
+    let dead_color = theme_color(theme, "deadCell", "#ffffff");
+    let palette = js_sys::Reflect::get(theme, &"palette".into()).ok()
+        .filter(|value| value.is_array())
+        .map(|value| value.unchecked_into::<js_sys::Array>());
+
+    let states = self.rule.states() as usize;
+
+    (0..states)
+        .map(|state| {
+            palette.as_ref()
+                .and_then(|palette| palette.get(state as u32).as_string())
+                .or_else(|| self.rule.color(state as u8))
+                .unwrap_or_else(|| match state {
+                    0 => dead_color.clone(),
+                    1 => alive_color.clone(),
+                    _ => blend_colors(
+                        &alive_color,
+                        &dead_color,
+                        (state - 1) as f64/(states - 1) as f64,
+                    ),
+                })
+        })
+        .collect()
+}}
+
 #[wasm_bindgen]
 impl Universe {
+/// Render the universe, or its viewport (see `set_viewport`), to a string,
+/// one line per row.
+///
+/// Like `render_to_context`, cells are rendered relative to the background of
+/// the universe (see `Universe::background`).
 pub fn render_to_string(&self) -> String {
+    let (left, top, width, height) = self.render_frame();
     let mut str = String::new();
-    for line in self.cells.as_slice().chunks(self.width as usize) {
-        for &cell in line {
-            match cell {
+    for row in top..top + height {
+        for col in left..left + width {
+            match self.neighbour(col, row) {
                 Cell::Dead => str.push('◻'),
                 Cell::Alive => str.push('◼'),
+                _ => str.push('▣'),
             }
         }
         str.push('\n');
@@ -18,7 +145,30 @@ pub fn render_to_string(&self) -> String {
     str
 }
 
-/// Render the universe to a canvas element.
+/// Render the universe, or its viewport (see `set_viewport`), to a canvas
+/// element.
+///
+/// The theme may give a color for each state of the rule in its `palette`
+/// array, see `Universe::palette`, e.g. one color per species with species
+/// rules. Otherwise, the states of cyclic and Greenberg-Hastings rules are
+/// drawn with colors spread evenly on a color wheel.
+///
+/// When the `hexagonal` flag of the theme is set, which is the default for
+/// rules using the hexagonal neighbourhood, cells are drawn as pointy-top
+/// hexagons, each row being shifted by half a cell to the left of the row
+/// above it, and rows being `(cellSize + 1)*sqrt(3)/2` pixels apart so that
+/// hexagons tile. The rendering is then `(height - 1)*(cellSize + 1)/2` pixels
+/// wider, and shorter.
+///
+/// Cells are rendered relative to the background of the universe (see
+/// `Universe::background`), so that rules containing B0 do not strobe.
+///
+/// When the theme gives a `zoneBoundary` color, the boundaries between the
+/// zones of the universe (see `add_zone`) are drawn with that color, on
+/// square grids only.
+///
+/// Turmites (see `add_turmite`) are drawn over the cells as arrows pointing
+/// to their heading, with the `turmite` color of the theme, red by default.
 pub fn render_to_context(
     &self,
     context: web_sys::CanvasRenderingContext2d,
@@ -29,31 +179,92 @@ pub fn render_to_context(
         Err(_) => 5.0,
     };
 
-    let alive_color =
-        js_sys::Reflect::get(&theme, &"aliveCell".into())
-            .unwrap_or(JsValue::from_str("#000000"));
+    let hexagonal = js_sys::Reflect::get(&theme, &"hexagonal".into()).ok()
+        .and_then(|value| value.as_bool())
+        .unwrap_or(self.rule.neighbourhood() == Neighbourhood::Hexagonal);
 
-    let dead_color =
-        js_sys::Reflect::get(&theme, &"deadCell".into())
-            .unwrap_or(JsValue::from_str("#ffffff"));
+    let palette = self.palette(&theme);
 
-    context.begin_path();
-    for (idx, cell) in self.cells.iter().copied().enumerate() {
-        if cell == Cell::Alive {
-            context.set_fill_style(&alive_color);
+    // the rendered rectangle, cells being drawn relative to its top left cell
+    let (left, top, width, height) = self.render_frame();
+    let cells = (top..top + height)
+        .flat_map(|row| (left..left + width).map(move |col| (col, row)))
+        .map(|(col, row)| (col - left, row - top, self.neighbour(col, row)));
+
+    // center of a cell, hexagons of width `cell_size` being spaced by one
+    // pixel as squares are
+    let center = |col: i32, row: i32| {
+        let pitch = cell_size + 1.0;
+        if hexagonal {
+            (
+                (col as f64)*pitch + 1.0 + cell_size/2.0 + ((height - 1 - row) as f64)*pitch/2.0,
+                (row as f64)*pitch*3f64.sqrt()/2.0 + 1.0 + cell_size/3f64.sqrt(),
+            )
         } else {
-            context.set_fill_style(&dead_color);
+            (
+                (col as f64)*pitch + 1.0 + cell_size/2.0,
+                (row as f64)*pitch + 1.0 + cell_size/2.0,
+            )
+        }
+    };
+
+    if hexagonal {
+        for (col, row, cell) in cells {
+            let state = (cell.state() as usize).min(palette.len() - 1);
+            context.set_fill_style_str(&palette[state]);
+
+            let (x, y) = center(col, row);
+            fill_hexagon(&context, x, y, cell_size);
         }
+    } else {
+        context.begin_path();
+        for (col, row, cell) in cells {
+            let state = (cell.state() as usize).min(palette.len() - 1);
+            context.set_fill_style_str(&palette[state]);
 
-        let (col, row) = self.get_coordinates(idx);
-        context.fill_rect(
-            (col as f64)*(cell_size + 1.0) + 1.0,
-            (row as f64)*(cell_size + 1.0) + 1.0,
-            cell_size,
-            cell_size,
-        );
+            context.fill_rect(
+                (col as f64)*(cell_size + 1.0) + 1.0,
+                (row as f64)*(cell_size + 1.0) + 1.0,
+                cell_size,
+                cell_size,
+            );
+        }
+        context.stroke();
+    }
+
+    let zone_boundary = js_sys::Reflect::get(&theme, &"zoneBoundary".into()).ok()
+        .and_then(|value| value.as_string());
+
+    if let (Some(color), Some(zones), false) = (zone_boundary, &self.zones, hexagonal) {
+        context.begin_path();
+        context.set_stroke_style_str(&color);
+        for row in 0.max(top)..self.height.min(top + height) {
+            for col in 0.max(left)..self.width.min(left + width) {
+                let zone = zones[self.get_index(col, row)];
+                let x = ((col - left + 1) as f64)*(cell_size + 1.0) + 0.5;
+                let y = ((row - top + 1) as f64)*(cell_size + 1.0) + 0.5;
+
+                if col + 1 < self.width && zones[self.get_index(col + 1, row)] != zone {
+                    context.move_to(x, y - cell_size - 1.0);
+                    context.line_to(x, y);
+                }
+                if row + 1 < self.height && zones[self.get_index(col, row + 1)] != zone {
+                    context.move_to(x - cell_size - 1.0, y);
+                    context.line_to(x, y);
+                }
+            }
+        }
+        context.stroke();
+    }
+
+    if !self.turmites.is_empty() {
+        context.set_fill_style_str(&theme_color(&theme, "turmite", "#ff0000"));
+        for turmite in self.turmites.iter() {
+            let (col, row) = turmite.position();
+            let (x, y) = center(col - left, row - top);
+            fill_arrow(&context, x, y, cell_size, turmite.heading());
+        }
     }
-    context.stroke();
 
     Ok(())
 }}
@@ -0,0 +1,232 @@
+use std::str::FromStr;
+
+use wasm_bindgen::prelude::*;
+
+use crate::*;
+
+/******************************************************************************
+ * Anchor
+ *****************************************************************************/
+
+/// The point of a universe staying in place when the universe is resized.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Get the horizontal and vertical positions of the anchor, from 0 for
+    /// the left or top edge to 2 for the right or bottom edge.
+    fn position(&self) -> (i32, i32) {
+        match self {
+            Anchor::TopLeft => (0, 0),
+            Anchor::Top => (1, 0),
+            Anchor::TopRight => (2, 0),
+            Anchor::Left => (0, 1),
+            Anchor::Center => (1, 1),
+            Anchor::Right => (2, 1),
+            Anchor::BottomLeft => (0, 2),
+            Anchor::Bottom => (1, 2),
+            Anchor::BottomRight => (2, 2),
+        }
+    }
+}
+
+pub enum ResizeError {
+    InvalidAnchor,
+    InvalidSize,
+}
+
+impl std::fmt::Display for ResizeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ResizeError::InvalidAnchor => write!(f, "invalid anchor"),
+            ResizeError::InvalidSize => write!(f, "invalid size"),
+        }
+    }
+}
+
+impl From<ResizeError> for JsValue {
+    fn from(err: ResizeError) -> Self {
+        JsValue::from_str(&err.to_string())
+    }
+}
+
+impl FromStr for Anchor {
+    type Err = ResizeError;
+
+    /// Parse "top-left", "top", "top-right", "left", "center", "right",
+    /// "bottom-left", "bottom" or "bottom-right", case insensitively.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "top-left" => Ok(Anchor::TopLeft),
+            "top" => Ok(Anchor::Top),
+            "top-right" => Ok(Anchor::TopRight),
+            "left" => Ok(Anchor::Left),
+            "center" => Ok(Anchor::Center),
+            "right" => Ok(Anchor::Right),
+            "bottom-left" => Ok(Anchor::BottomLeft),
+            "bottom" => Ok(Anchor::Bottom),
+            "bottom-right" => Ok(Anchor::BottomRight),
+            _ => Err(ResizeError::InvalidAnchor),
+        }
+    }
+}
+
+/******************************************************************************
+ * Universe
+ *****************************************************************************/
+
+impl Universe {
+    /// Move the frame of the universe to the rectangle of `width` by `height`
+    /// cells whose top left cell is at (`left`, `top`), see
+    /// `Universe::reframe`, giving the cells which are not dead out of the new
+    /// frame as an array [col, row, state, col, row, state, ...].
+    fn resize_frame(&mut self, left: i32, top: i32, width: i32, height: i32) -> Vec<i32> {
+        let (origin_col, origin_row) = self.origin;
+        let removed = self.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, &cell)| cell != Cell::Dead)
+            .map(|(idx, &cell)| (self.get_coordinates(idx), cell))
+            .filter(|&((col, row), _)| {
+                !(left..left + width).contains(&col) || !(top..top + height).contains(&row)
+            })
+            .flat_map(|((col, row), cell)| {
+                vec![col + origin_col, row + origin_row, cell.state() as i32]
+            })
+            .collect();
+
+        self.reframe(left, top, width, height);
+        removed
+    }
+
+    /// Get the translation of the cells of the universe as an array [dx, dy]
+    /// once its frame is moved to (`left`, `top`), cells keeping their
+    /// coordinates in an unbounded universe (see `Universe::origin`).
+    fn translation(&self, left: i32, top: i32) -> Vec<i32> {
+        match self.topology {
+            Topology::Unbounded => vec![0, 0],
+            _ => vec![-left, -top],
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+/// Resize the universe to `width` by `height` cells, the `anchor` of the
+/// universe, i.e. "top-left", "top", "top-right", "left", "center", "right",
+/// "bottom-left", "bottom" or "bottom-right", staying in place.
+///
+/// The cells which are not dead and do not fit in the new size are removed,
+/// and returned as an array [col, row, state, col, row, state, ...] giving
+/// their coordinates before the resize. Resizing back to the former size
+/// with the same anchor, then setting these cells, undoes the resize.
+///
+/// Cells, zones and turmites are moved with the anchor, the cells of the
+/// previous generation in reversible mode too.
+///
+/// A sphere must stay square, and Margolus rules need an even width and
+/// height (see `set_rule`).
+pub fn resize(&mut self, width: u32, height: u32, anchor: &str) -> Result<Vec<i32>, ResizeError> {
+    let (x, y) = anchor.parse::<Anchor>()?.position();
+    let width = 1.max(width as i32);
+    let height = 1.max(height as i32);
+
+    if self.topology == Topology::Sphere && width != height
+        || !self.rule.fits(width, height) {
+        return Err(ResizeError::InvalidSize);
+    }
+
+    // half differences are rounded toward 0, so that resizing back with the
+    // same anchor moves the cells back
+    let left = (self.width - width)*x/2;
+    let top = (self.height - height)*y/2;
+
+    Ok(self.resize_frame(left, top, width, height))
+}
+
+/// Resize the universe to the bounding box of its cells which are not dead,
+/// with `margin` dead cells on each side, returning the translation of the
+/// cells as an array [dx, dy].
+///
+/// An empty universe is left unchanged.
+pub fn fit_to_pattern(&mut self, margin: u32) -> Vec<i32> {
+    let bounds = self.cells
+        .iter()
+        .enumerate()
+        .filter(|(_, &cell)| cell != Cell::Dead)
+        .map(|(idx, _)| self.get_coordinates(idx))
+        .fold(None, |bounds, (col, row)| match bounds {
+            None => Some((col, row, col, row)),
+            Some((left, top, right, bottom)) => Some((
+                left.min(col),
+                top.min(row),
+                right.max(col),
+                bottom.max(row),
+            )),
+        });
+
+    let (left, top, right, bottom) = match bounds {
+        Some(bounds) => bounds,
+        None => return vec![0, 0],
+    };
+
+    let margin = margin as i32;
+    let (left, top) = (left - margin, top - margin);
+    let (mut width, mut height) = (right + margin + 1 - left, bottom + margin + 1 - top);
+
+    // a sphere stays square, growing to the right or at the bottom
+    if self.topology == Topology::Sphere {
+        width = width.max(height);
+        height = width;
+    }
+
+    // Margolus rules need an even width and height, likewise
+    if !self.rule.fits(width, height) {
+        width += width%2;
+        height += height%2;
+    }
+
+    self.resize_frame(left, top, width, height);
+    self.translation(left, top)
+}
+
+/// Grow the universe so that it contains the rectangle of `width` by `height`
+/// cells whose top left cell is at (`col`, `row`), e.g. to paste a pattern,
+/// returning the translation of the cells as an array [dx, dy].
+///
+/// The universe never shrinks, and no cell is removed.
+pub fn grow_to_contain(&mut self, col: i32, row: i32, width: u32, height: u32) -> Vec<i32> {
+    let (col, row) = (col - self.origin.0, row - self.origin.1);
+    let left = col.min(0);
+    let top = row.min(0);
+    let mut right = (col + 1.max(width as i32)).max(self.width);
+    let mut bottom = (row + 1.max(height as i32)).max(self.height);
+
+    // a sphere stays square
+    if self.topology == Topology::Sphere {
+        let size = (right - left).max(bottom - top);
+        right = left + size;
+        bottom = top + size;
+    }
+
+    // Margolus rules need an even width and height
+    if !self.rule.fits(right - left, bottom - top) {
+        right += (right - left)%2;
+        bottom += (bottom - top)%2;
+    }
+
+    if (left, top, right, bottom) != (0, 0, self.width, self.height) {
+        self.resize_frame(left, top, right - left, bottom - top);
+    }
+    self.translation(left, top)
+}}
@@ -0,0 +1,110 @@
+use wasm_bindgen::prelude::*;
+
+use crate::*;
+
+/******************************************************************************
+ * Second-order automaton
+ *****************************************************************************/
+
+impl Universe {
+    /// Clear the cells of the previous generation, if the universe is in
+    /// reversible mode.
+    pub(crate) fn clear_previous(&mut self) {
+        if let Some(previous) = self.previous.as_mut() {
+            *previous = vec![Cell::Dead; self.cells.len()];
+        }
+    }
+
+    /// Replace the current and the previous generations by the next and the
+    /// current generations, the next state of a cell being the state given by
+    /// the rule minus its previous state, modulo the number of states.
+    ///
+    /// With 2 states rules, the next state is thus the state given by the
+    /// rule XOR the previous state. Each cell follows the rule of its zone
+    /// (see `add_zone`).
+    pub(crate) fn second_order_step(&mut self) {
+        let states = self.rule.states();
+        let next = self.zoned_generation();
+        let previous = self.previous.take().unwrap_or_default();
+
+        let cells = next
+            .iter()
+            .zip(previous.iter())
+            .map(|(&next, &previous)| {
+                let state = (next.state() as u16 + states - previous.state() as u16)%states;
+                Cell::from(state as u8)
+            })
+            .collect();
+
+        self.previous = Some(std::mem::replace(&mut *self.cells, cells));
+    }
+
+    fn swap_generations(&mut self) {
+        if let Some(previous) = self.previous.as_mut() {
+            std::mem::swap(&mut *self.cells, previous);
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+/// Check if the universe is in reversible mode.
+pub fn reversible(&self) -> bool {
+    self.previous.is_some()
+}
+
+/// Set whether the universe is in reversible mode.
+///
+/// In reversible mode, the universe keeps its previous generation, and the
+/// next state of a cell is the state given by the rule minus its previous
+/// state modulo the number of states, i.e. the state given by the rule XOR
+/// its previous state with 2 states rules. Any run can then be stepped
+/// backwards exactly with `untick`.
+///
+/// The previous generation is dead when the mode is set, and rules with B0
+/// are not emulated (see `Universe::background`) in reversible mode: cells
+/// keep their actual state, the background staying dead.
+pub fn set_reversible(&mut self, reversible: bool) {
+    if reversible {
+        self.clear_background();
+        self.previous = Some(vec![Cell::Dead; self.cells.len()]);
+    } else {
+        self.previous = None;
+    }
+}
+
+/// Compute the previous generation of the universe, in reversible mode.
+///
+/// Does nothing if the universe is not in reversible mode.
+pub fn untick(&mut self) {
+    if self.previous.is_none() {
+        return;
+    }
+
+    // stepping forward from (previous, current) gives (before previous,
+    // previous)
+    self.generation = self.generation.saturating_sub(1);
+    self.swap_generations();
+    self.second_order_step();
+    self.swap_generations();
+}
+
+/// Create a universe in reversible mode from the RLE encoding of its current
+/// and previous generations.
+///
+/// Both generations must have the same size, the rule of the universe being
+/// the rule of the current generation.
+pub fn from_rle_layers(
+    current: JsValue,
+    previous: JsValue,
+) -> Result<Universe, RLEDecoderError> {
+    let mut universe = Universe::from_rle(current)?;
+    let previous = Universe::from_rle(previous)?;
+
+    if previous.width != universe.width || previous.height != universe.height {
+        return Err(RLEDecoderError::InvalidHeader);
+    }
+
+    universe.previous = Some(previous.cells.into());
+    Ok(universe)
+}}
@@ -7,38 +7,60 @@ use crate::*;
 /******************************************************************************
  * Encoder
  *****************************************************************************/
-type RLEContent = Vec<(usize, char)>;
+type RLEContent = Vec<(usize, String)>;
+
+/// Get the RLE tag of a cell.
+///
+/// Two states rules use the "b" and "o" tags, multi-state rules use "." for
+/// the dead state and "A" to "X", "pA" to "pX", ..., "yA" to "yO" for the
+/// states 1 to 255.
+fn cell_tag(cell: Cell, states: u16) -> String {
+    match (states, cell.state()) {
+        (2, 0) => String::from("b"),
+        (2, _) => String::from("o"),
+        (_, 0) => String::from("."),
+        (_, state) => {
+            let prefix = (state - 1)/24;
+            let letter = (b'A' + (state - 1)%24) as char;
+
+            if prefix == 0 {
+                letter.to_string()
+            } else {
+                format!("{}{}", (b'p' + prefix - 1) as char, letter)
+            }
+        },
+    }
+}
 
 #[wasm_bindgen]
 impl Universe {
+/// Write the universe in RLE format.
+///
+/// Cells are written with their actual state, as Golly does, whatever the
+/// background of the universe (see `background`).
 pub fn to_rle(&self) -> String {
+    let states = self.rule.states();
+    let background = self.background.state();
     let rle_content = self.cells
         .chunks(self.width as usize)
         .enumerate()
         .flat_map(|(row_index, cells)| {
-            let mut row = cells
-                .iter().map(|cell| match cell {
-                    Cell::Alive => 'o',
-                    Cell::Dead => 'b',
-                })
-                .collect::<Vec<char>>();
-
             // remove trailing dead cells
-            if let Some(last_alive_index) = row
+            let len = cells
                 .iter()
-                .rev()
-                .position(|&c| c == 'o')
-            {
-                row.truncate(cells.len() - last_alive_index);
-            } else {
-                row.clear();
-            }
-            
-            row.push(if row_index as i32 == self.height - 1 {
-                '!'
+                .rposition(|&cell| cell.state() != background)
+                .map_or(0, |last_alive_index| last_alive_index + 1);
+
+            let mut row = cells[..len]
+                .iter()
+                .map(|&cell| cell_tag(Cell::from(cell.state() ^ background), states))
+                .collect::<Vec<String>>();
+
+            row.push(String::from(if row_index as i32 == self.height - 1 {
+                "!"
             } else {
-                '$'
-            });
+                "$"
+            }));
             row
         })
         .fold(RLEContent::new(), |mut rle_content, c| {
@@ -53,13 +75,30 @@ pub fn to_rle(&self) -> String {
             rle_content
         });
 
-    let mut rle = String::new();
+    // the origin of an unbounded universe, as a "#R" line giving the
+    // coordinates of its top left cell as XLife does, the generation of rules
+    // depending on its phase, as a "#G" line, then turmites, as comment lines
+    let mut rle = match (self.topology, self.origin) {
+        (Topology::Unbounded, (col, row)) if (col, row) != (0, 0) => {
+            format!("#R {} {}\n", col, row)
+        },
+        _ => String::new(),
+    };
+    if self.rule.has_phase() && self.generation != 0 {
+        rle.push_str(&format!("#G {}\n", self.generation));
+    }
+    rle.push_str(&self.turmite_comments());
 
-    // header
+    // header, with the topology as a Golly bounded grid unless the universe
+    // is a plain torus
     rle.push_str(&format!(
-        "x = {}, y = {}, rule = B3/S23",
-        self.width, self.height
+        "x = {}, y = {}, rule = {}",
+        self.width, self.height, self.rule
     ));
+    if self.topology != Topology::default() {
+        rle.push(':');
+        rle.push_str(&self.topology.grid(self.width, self.height));
+    }
 
     // content
     let rle_content_str = rle_content
@@ -73,8 +112,8 @@ pub fn to_rle(&self) -> String {
         .collect::<Vec<u8>>();
 
     rle_content_str.chunks(70).for_each(|chunk| {
-        rle.push_str("\n");
-        rle.push_str(&String::from_utf8(chunk.to_vec()).unwrap());
+        rle.push('\n');
+        rle.push_str(std::str::from_utf8(chunk).unwrap());
     });
 
     rle
@@ -90,6 +129,10 @@ pub enum RLEDecoderError {
     InvalidNumber,
     InvalidTag,
     InvalidHeader,
+    InvalidRule,
+    InvalidTurmite,
+    InvalidTopology,
+    InvalidPosition,
 }
 
 impl std::fmt::Display for RLEDecoderError {
@@ -100,6 +143,10 @@ impl std::fmt::Display for RLEDecoderError {
             RLEDecoderError::InvalidNumber => write!(f, "invalid number"),
             RLEDecoderError::InvalidTag => write!(f, "invalid tag"),
             RLEDecoderError::InvalidHeader => write!(f, "invalid header"),
+            RLEDecoderError::InvalidRule => write!(f, "invalid rule"),
+            RLEDecoderError::InvalidTurmite => write!(f, "invalid turmite"),
+            RLEDecoderError::InvalidTopology => write!(f, "invalid topology"),
+            RLEDecoderError::InvalidPosition => write!(f, "invalid position"),
         }
     }
 }
@@ -149,7 +196,7 @@ impl RLETokenIterator {
 
         self.prev = self.curr;
 
-        return Ok(RLEToken::Count(v));
+        Ok(RLEToken::Count(v))
     }
 
     fn read_tag_token(&mut self) -> Result<RLEToken, RLEDecoderError> {
@@ -159,15 +206,31 @@ impl RLETokenIterator {
 
         let token = match bytes[self.curr] {
             b'o' => RLEToken::Cell(Cell::Alive),
-            b'b' => RLEToken::Cell(Cell::Dead),
+            b'b' | b'.' => RLEToken::Cell(Cell::Dead),
             b'$' => RLEToken::EndOfLine,
+            letter @ b'A'..=b'X' => {
+                RLEToken::Cell(Cell::from(letter - b'A' + 1))
+            },
+            prefix @ b'p'..=b'y' => {
+                let state = match bytes.get(self.curr + 1) {
+                    Some(letter @ b'A'..=b'X') => {
+                        24*(prefix - b'p' + 1) as u32 + (letter - b'A' + 1) as u32
+                    },
+                    _ => return Err(RLEDecoderError::InvalidTag),
+                };
+
+                check_if(state <= 255, RLEDecoderError::InvalidTag)?;
+
+                self.curr += 1;
+                RLEToken::Cell(Cell::from(state as u8))
+            },
             _ => return Err(RLEDecoderError::InternalError),
         };
 
         self.curr += 1;
         self.prev = self.curr;
 
-        return Ok(token);
+        Ok(token)
     }
 }
 
@@ -181,12 +244,12 @@ impl Iterator for RLETokenIterator {
             return None;
         }
 
-        return match bytes[self.curr] {
+        match bytes[self.curr] {
             b'0'|b'1'|b'2'|b'3'|b'4'|b'5'|b'6'|b'7'|b'8'|b'9' => {
                 // number
                 Some(self.read_number_token())
             },
-            b'o'|b'b'|b'$' => {
+            b'o'|b'b'|b'$'|b'.'|b'A'..=b'X'|b'p'..=b'y' => {
                 // tag
                 Some(self.read_tag_token())
             },
@@ -194,13 +257,16 @@ impl Iterator for RLETokenIterator {
                 None
             },
             _ => {
-                return Some(Err(RLEDecoderError::InvalidTag));
+                Some(Err(RLEDecoderError::InvalidTag))
             },
         }
     }
 }
 
-pub fn parse_size_value(dim: &str, s: &str) -> Result<u32, RLEDecoderError> {
+fn parse_header_value<'a>(
+    key: &str,
+    s: &'a str,
+) -> Result<&'a str, RLEDecoderError> {
     let parts = s
         .trim()
         .split('=')
@@ -208,9 +274,64 @@ pub fn parse_size_value(dim: &str, s: &str) -> Result<u32, RLEDecoderError> {
         .collect::<Vec<_>>();
 
     check_if(parts.len() == 2, RLEDecoderError::InvalidHeader)?;
-    check_if(parts[0] == dim, RLEDecoderError::InvalidHeader)?;
+    check_if(parts[0] == key, RLEDecoderError::InvalidHeader)?;
 
-    parts[1].parse::<u32>().or(Err(RLEDecoderError::InvalidHeader))
+    Ok(parts[1])
+}
+
+pub fn parse_size_value(dim: &str, s: &str) -> Result<u32, RLEDecoderError> {
+    parse_header_value(dim, s)?
+        .parse::<u32>()
+        .or(Err(RLEDecoderError::InvalidHeader))
+}
+
+pub fn parse_rule_value(s: &str) -> Result<Rule, RLEDecoderError> {
+    parse_header_value("rule", s)?
+        .parse::<Rule>()
+        .or(Err(RLEDecoderError::InvalidRule))
+}
+
+pub fn parse_grid_value(s: &str) -> Result<(Topology, u32, u32), RLEDecoderError> {
+    Topology::parse_grid(s)
+        .or(Err(RLEDecoderError::InvalidTopology))
+}
+
+/// Parse the position of the top left cell of a pattern given by a "#R" or
+/// "#P" comment line, if any.
+pub fn parse_position_comment<'a>(
+    mut lines: impl Iterator<Item = &'a str>,
+) -> Result<Option<(i32, i32)>, RLEDecoderError> {
+    let position = match lines.find_map(|line| {
+        line.strip_prefix("#R").or_else(|| line.strip_prefix("#P"))
+    }) {
+        Some(position) => position,
+        None => return Ok(None),
+    };
+
+    let values = position
+        .split_whitespace()
+        .map(|value| value.parse::<i32>())
+        .collect::<Result<Vec<_>, _>>()
+        .or(Err(RLEDecoderError::InvalidPosition))?;
+
+    match values[..] {
+        [col, row] => Ok(Some((col, row))),
+        _ => Err(RLEDecoderError::InvalidPosition),
+    }
+}
+
+/// Parse the generation given by a "#G" comment line, if any.
+pub fn parse_generation_comment<'a>(
+    mut lines: impl Iterator<Item = &'a str>,
+) -> Result<Option<u32>, RLEDecoderError> {
+    match lines.find_map(|line| line.strip_prefix("#G")) {
+        Some(generation) => generation
+            .trim()
+            .parse::<u32>()
+            .map(Some)
+            .or(Err(RLEDecoderError::InvalidNumber)),
+        None => Ok(None),
+    }
 }
 
 #[wasm_bindgen]
@@ -223,28 +344,56 @@ pub fn from_rle(value: JsValue) -> Result<Universe, RLEDecoderError> {
         .lines()
         .filter(|line| !line.starts_with("#"));
 
-    // parse the header
+    // parse the header, the rule may itself contain commas
     let headers = lines
         .next().ok_or(RLEDecoderError::InvalidHeader)?
-        .split(',').map(str::trim).collect::<Vec<_>>();
+        .splitn(3, ',').map(str::trim).collect::<Vec<_>>();
+
+    check_if(headers.len() >= 2, RLEDecoderError::InvalidHeader)?;
 
     let width = parse_size_value("x", headers[0])?;
     let height = parse_size_value("y", headers[1])?;
 
+    // the rule may be followed by a Golly bounded grid, e.g. "B3/S23:T30,20",
+    // giving the topology and the size of the universe
+    let (rule, grid) = match headers.get(2).map(|header| header.rsplit_once(':')) {
+        Some(Some((rule, grid))) => (parse_rule_value(rule)?, Some(parse_grid_value(grid)?)),
+        Some(None) => (parse_rule_value(headers[2])?, None),
+        None => (Rule::default(), None),
+    };
+    let (topology, width, height) = match grid {
+        Some((Topology::Unbounded, _, _)) => (Topology::Unbounded, width, height),
+        Some(grid) => {
+            check_if(rule.fits(grid.1 as i32, grid.2 as i32), RLEDecoderError::InvalidRule)?;
+            grid
+        },
+        // Margolus rules need an even width and height, the size of the
+        // pattern being rounded up
+        None if !rule.fits(width as i32, height as i32) => {
+            (Topology::default(), width + width%2, height + height%2)
+        },
+        None => (Topology::default(), width, height),
+    };
+
     let mut universe = Universe::new(width, height);
-    let mut it = RLETokenIterator::new(String::from_iter(lines));
+    universe.rule = rule;
+    universe.topology = topology;
+    universe.read_turmite_comments(
+        rle_string.lines().filter(|line| line.starts_with("#"))
+    )?;
+    let it = RLETokenIterator::new(String::from_iter(lines));
     let mut count = 1;
     let mut row = 0;
     let mut col = 0;
 
-    while let Some(token) = it.next() {
+    for token in it {
         match token? {
             RLEToken::Count(value) => {
                 count = value;
             },
             RLEToken::Cell(cell) => {
                 (0..count).for_each(|i| {
-                    universe.set_cell(col + i, row, cell);
+                    universe.set_cell(col + i, row, cell.state()).ok();
                 });
                 col += count;
                 count = 1;
@@ -257,5 +406,20 @@ pub fn from_rle(value: JsValue) -> Result<Universe, RLEDecoderError> {
         }
     }
 
+    // cells are read relative to the top left cell of the pattern, which is
+    // the origin of an unbounded universe
+    let position = parse_position_comment(
+        rle_string.lines().filter(|line| line.starts_with("#"))
+    )?;
+    if let (Topology::Unbounded, Some(position)) = (universe.topology, position) {
+        universe.origin = position;
+    }
+
+    // the generation gives the phase of alternating and Margolus rules
+    universe.generation = parse_generation_comment(
+        rle_string.lines().filter(|line| line.starts_with("#"))
+    )?.unwrap_or(0);
+
+    universe.find_current_row();
     Ok(universe)
 }}
@@ -0,0 +1,93 @@
+use std::str::FromStr;
+
+use super::*;
+
+/******************************************************************************
+ * Rule
+ *****************************************************************************/
+
+/// A sequence of rules applied cyclically, the rule computing a generation
+/// being given by the generation number modulo the length of the sequence.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AlternatingRule {
+    rules: Vec<Rule>,
+}
+
+impl AlternatingRule {
+    /// Create a rule from a sequence of at least 2 rules.
+    pub fn new(rules: Vec<Rule>) -> Option<Self> {
+        if rules.len() < 2 {
+            return None;
+        }
+        Some(AlternatingRule { rules })
+    }
+
+    /// Get the sequence of rules.
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Get the rule computing the next generation of a universe.
+    pub fn current(&self, universe: &Universe) -> &Rule {
+        &self.rules[universe.generation as usize%self.rules.len()]
+    }
+
+    /// Get the number of states of the rule, i.e. the largest number of
+    /// states of its rules.
+    pub fn states(&self) -> u16 {
+        self.rules.iter().map(Rule::states).max().unwrap_or(2)
+    }
+
+    /// Get the default color of a state, as given by the first rule defining
+    /// one.
+    pub fn color(&self, state: u8) -> Option<String> {
+        self.rules.iter().find_map(|rule| rule.color(state))
+    }
+}
+
+/******************************************************************************
+ * Encoder
+ *****************************************************************************/
+
+impl std::fmt::Display for AlternatingRule {
+    /// Write the rules separated by hyphens, e.g. "B3/S23-B36/S125".
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let rules = self.rules
+            .iter()
+            .map(Rule::to_string)
+            .collect::<Vec<_>>();
+
+        write!(f, "{}", rules.join("-"))
+    }
+}
+
+/******************************************************************************
+ * Decoder
+ *****************************************************************************/
+
+/// Split a string at hyphens into rules, trying the first hyphens first so
+/// that hyphens of Hensel notation (e.g. "B2-a/S12") are kept within rules.
+fn parse_rules(s: &str) -> Option<Vec<Rule>> {
+    if let Ok(rule) = parse_rule(s) {
+        return Some(vec![rule]);
+    }
+
+    s.match_indices('-').find_map(|(i, _)| {
+        let rule = parse_rule(&s[..i]).ok()?;
+        let mut rules = parse_rules(&s[i + 1..])?;
+
+        rules.insert(0, rule);
+        Some(rules)
+    })
+}
+
+impl FromStr for AlternatingRule {
+    type Err = RuleParseError;
+
+    /// Parse at least 2 rules separated by hyphens, e.g. "B3/S23-B36/S125".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_rules(s)
+            .and_then(AlternatingRule::new)
+            .ok_or(RuleParseError::InvalidFormat)
+    }
+}
@@ -0,0 +1,781 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::str::FromStr;
+
+use wasm_bindgen::prelude::*;
+
+use super::*;
+
+/******************************************************************************
+ * Neighbourhoods
+ *****************************************************************************/
+
+/// Offsets of the cells of a @TABLE transition, in Golly's order.
+const MOORE_OFFSETS: [(i32, i32); 9] = [
+    (0, 0), (0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1),
+];
+const VON_NEUMANN_OFFSETS: [(i32, i32); 5] = [
+    (0, 0), (0, -1), (1, 0), (0, 1), (-1, 0),
+];
+const HEXAGONAL_OFFSETS: [(i32, i32); 7] = [
+    (0, 0), (0, -1), (1, 0), (1, 1), (0, 1), (-1, 0), (-1, -1),
+];
+const ONE_DIMENSIONAL_OFFSETS: [(i32, i32); 3] = [
+    (0, 0), (-1, 0), (1, 0),
+];
+
+/// Offsets of the cells of a @TREE, in Golly's order, from the root of the
+/// tree down to its leaves.
+const TREE_MOORE_OFFSETS: [(i32, i32); 9] = [
+    (-1, -1), (1, -1), (-1, 1), (1, 1), (0, -1), (-1, 0), (1, 0), (0, 1), (0, 0),
+];
+const TREE_VON_NEUMANN_OFFSETS: [(i32, i32); 5] = [
+    (0, -1), (-1, 0), (1, 0), (0, 1), (0, 0),
+];
+
+/// Get the permutations of the neighbours (center cell excluded) of a @TABLE
+/// transition for the given symmetries, or `None` if the symmetries do not
+/// exist for that number of neighbours.
+///
+/// Neighbours being listed clockwise, rotations shift them, and reflections
+/// reverse their order, keeping the first one in place. The 2 neighbours of
+/// one-dimensional rules, west then east, are swapped by reflections.
+fn symmetry_permutations(symmetries: &str, neighbours: usize) -> Option<Vec<Vec<usize>>> {
+    let (shift, reflect) = match (symmetries, neighbours) {
+        ("none", _) => (neighbours, false),
+        ("reflect", 2) => (2, true),
+        ("reflect_horizontal", 4) | ("reflect_horizontal", 8) => (neighbours, true),
+        ("rotate2", 6) => (3, false),
+        ("rotate3", 6) => (2, false),
+        ("rotate4", 4) | ("rotate6", 6) | ("rotate8", 8) => (1, false),
+        ("rotate4", 8) => (2, false),
+        ("rotate4reflect", 4) | ("rotate6reflect", 6) | ("rotate8reflect", 8) => (1, true),
+        ("rotate4reflect", 8) => (2, true),
+        _ => return None,
+    };
+
+    // the reflection of the neighbour `i` is the neighbour `mirror - i`
+    let mirror = match neighbours {
+        2 => 1,
+        _ => neighbours,
+    };
+
+    let mut permutations = Vec::new();
+    for rotation in (0..neighbours).step_by(shift) {
+        let rotated = (0..neighbours)
+            .map(|i| (i + rotation)%neighbours)
+            .collect::<Vec<_>>();
+        if reflect {
+            permutations.push(
+                (0..neighbours)
+                    .map(|i| rotated[(mirror - i)%neighbours])
+                    .collect(),
+            );
+        }
+        permutations.push(rotated);
+    }
+
+    Some(permutations)
+}
+
+/// Get all the distinct arrangements of a list of items.
+fn distinct_permutations<T: Clone + Eq + std::hash::Hash>(items: &[T]) -> Vec<Vec<T>> {
+    if items.is_empty() {
+        return vec![Vec::new()];
+    }
+
+    let mut seen = HashSet::new();
+    let mut permutations = Vec::new();
+
+    for (i, item) in items.iter().enumerate() {
+        if seen.insert(item) {
+            let mut rest = items.to_vec();
+            rest.remove(i);
+            for mut permutation in distinct_permutations(&rest) {
+                permutation.insert(0, item.clone());
+                permutations.push(permutation);
+            }
+        }
+    }
+
+    permutations
+}
+
+/******************************************************************************
+ * Transitions
+ *****************************************************************************/
+
+/// A transition of a @TABLE, that is the set of states matching each cell of
+/// the neighbourhood, and the next state of the center cell.
+type Transition = (Vec<Vec<u8>>, u8);
+
+/// The transitions of a @TABLE, compiled to one set of matching transitions
+/// per cell of the neighbourhood and per state, so that the first matching
+/// transition is found with a few bitwise operations.
+#[derive(Debug, PartialEq, Eq)]
+struct TransitionTable {
+    offsets: Vec<(i32, i32)>,
+    states: usize,
+    words: usize,
+    masks: Vec<u64>,
+    outputs: Vec<u8>,
+}
+
+impl TransitionTable {
+    fn new(
+        offsets: Vec<(i32, i32)>,
+        states: usize,
+        transitions: &[Transition],
+    ) -> Self {
+        let words = transitions.len().div_ceil(64);
+        let mut masks = vec![0; offsets.len()*states*words];
+
+        for (index, (inputs, _)) in transitions.iter().enumerate() {
+            for (position, input) in inputs.iter().enumerate() {
+                for &state in input {
+                    let word = (position*states + state as usize)*words + index/64;
+                    masks[word] |= 1 << (index%64);
+                }
+            }
+        }
+
+        TransitionTable {
+            offsets,
+            states,
+            words,
+            masks,
+            outputs: transitions.iter().map(|&(_, output)| output).collect(),
+        }
+    }
+
+    /// Get the next state of the first transition matching the given states,
+    /// if any.
+    fn next_state(&self, states: &[u8]) -> Option<u8> {
+        if states.iter().any(|&state| state as usize >= self.states) {
+            return None;
+        }
+
+        (0..self.words).find_map(|word| {
+            let matching = states
+                .iter()
+                .enumerate()
+                .fold(!0u64, |matching, (position, &state)| {
+                    matching
+                        & self.masks[(position*self.states + state as usize)*self.words + word]
+                });
+
+            if matching != 0 {
+                Some(self.outputs[64*word + matching.trailing_zeros() as usize])
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// The nodes of a @TREE, each node having one child per state, the children
+/// of the nodes of the last level being the next states.
+#[derive(Debug, PartialEq, Eq)]
+struct TransitionTree {
+    offsets: Vec<(i32, i32)>,
+    states: usize,
+    children: Vec<u32>,
+    root: usize,
+}
+
+impl TransitionTree {
+    fn next_state(&self, states: &[u8]) -> Option<u8> {
+        if states.iter().any(|&state| state as usize >= self.states) {
+            return None;
+        }
+
+        let node = states
+            .iter()
+            .fold(self.root, |node, &state| {
+                self.children[node*self.states + state as usize] as usize
+            });
+        Some(node as u8)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Transitions {
+    Table(TransitionTable),
+    Tree(TransitionTree),
+}
+
+impl Transitions {
+    fn offsets(&self) -> &[(i32, i32)] {
+        match self {
+            Transitions::Table(table) => &table.offsets,
+            Transitions::Tree(tree) => &tree.offsets,
+        }
+    }
+
+    fn next_state(&self, states: &[u8]) -> Option<u8> {
+        match self {
+            Transitions::Table(table) => table.next_state(states),
+            Transitions::Tree(tree) => tree.next_state(states),
+        }
+    }
+}
+
+/******************************************************************************
+ * Rule
+ *****************************************************************************/
+
+thread_local! {
+    /// The rules loaded so far, by name.
+    static CUSTOM_RULES: RefCell<HashMap<String, Rc<CustomRule>>> =
+        RefCell::new(HashMap::new());
+}
+
+/// A rule loaded from a Golly rule file.
+///
+/// The transitions of the rule are given either by a @TABLE section, with
+/// variables and symmetries, or by a @TREE section. Cells for which no
+/// transition matches keep their state. The @COLORS section gives the default
+/// colors of the states.
+///
+/// Rules are registered by name once loaded, so that they can then be set by
+/// name, e.g. from the header of an RLE file.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CustomRule {
+    name: String,
+    states: u16,
+    neighbourhood: Neighbourhood,
+    transitions: Transitions,
+    colors: Vec<Option<String>>,
+}
+
+impl CustomRule {
+    /// Get a rule previously loaded, by name.
+    pub fn registered(name: &str) -> Option<Rc<CustomRule>> {
+        CUSTOM_RULES.with(|rules| rules.borrow().get(name.trim()).cloned())
+    }
+
+    /// Register a rule so that it can then be parsed by name.
+    pub fn register(rule: Rc<CustomRule>) {
+        CUSTOM_RULES.with(|rules| {
+            rules.borrow_mut().insert(rule.name.clone(), rule);
+        });
+    }
+
+    /// Get the name of the rule.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the number of states of the rule.
+    pub fn states(&self) -> u16 {
+        self.states
+    }
+
+    /// Get the neighbourhood of the rule.
+    pub fn neighbourhood(&self) -> Neighbourhood {
+        self.neighbourhood
+    }
+
+    /// Get the color of a state given by the @COLORS section, if any.
+    pub fn color(&self, state: u8) -> Option<String> {
+        self.colors.get(state as usize).cloned().flatten()
+    }
+
+    /// Compute the next state of the cell at (`col`, `row`) of a universe,
+    /// cells matching no transition keeping their state.
+    pub fn next_cell(&self, universe: &Universe, col: i32, row: i32) -> Cell {
+        let states = self.transitions
+            .offsets()
+            .iter()
+            .map(|&(delta_col, delta_row)| universe.neighbour(col + delta_col, row + delta_row).state())
+            .collect::<Vec<_>>();
+
+        match self.transitions.next_state(&states) {
+            Some(state) => Cell::from(state),
+            None => universe.cells[universe.get_index(col, row)],
+        }
+    }
+
+    /// Compute the next generation of a universe.
+    ///
+    /// The next states are cached by neighbourhood during the computation.
+    pub fn next_generation(&self, universe: &Universe) -> Vec<Cell> {
+        let offsets = self.transitions.offsets();
+        let mut cache = HashMap::new();
+        let mut cells = universe.cells.clone();
+
+        for row in 0..universe.height {
+            for col in 0..universe.width {
+                let mut states = [0u8; 9];
+                for (state, &(delta_col, delta_row)) in states.iter_mut().zip(offsets) {
+                    *state = universe.neighbour(col + delta_col, row + delta_row).state();
+                }
+
+                let next_state = *cache
+                    .entry(states)
+                    .or_insert_with(|| {
+                        self.transitions.next_state(&states[..offsets.len()])
+                    });
+
+                if let Some(state) = next_state {
+                    cells[universe.get_index(col, row)] = Cell::from(state);
+                }
+            }
+        }
+
+        cells
+    }
+}
+
+/******************************************************************************
+ * Encoder
+ *****************************************************************************/
+
+impl std::fmt::Display for CustomRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/******************************************************************************
+ * Decoder
+ *****************************************************************************/
+
+fn check_if(cond: bool, err: RuleParseError) -> Result<(), RuleParseError> {
+    if cond { Ok(()) } else { Err(err) }
+}
+
+/// Parse a `key:value` or `key=value` line of a @TABLE or @TREE section.
+fn parse_key_value<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    line.strip_prefix(key)
+        .map(str::trim_start)
+        .and_then(|value| value.strip_prefix([':', '=']))
+        .map(str::trim)
+}
+
+fn parse_state(s: &str, states: u16) -> Result<u8, RuleParseError> {
+    s.trim()
+        .parse::<u16>().ok()
+        .filter(|&state| state < states)
+        .map(|state| state as u8)
+        .ok_or(RuleParseError::InvalidStates)
+}
+
+/// Parse a set of states (e.g. "{0,1,a}"), the elements being states or
+/// variables, or a single element (e.g. "a").
+fn parse_state_set(
+    s: &str,
+    states: u16,
+    variables: &HashMap<String, Vec<u8>>,
+) -> Result<Vec<u8>, RuleParseError> {
+    let s = s.trim();
+    let elements = match s.strip_prefix('{') {
+        Some(elements) => elements
+            .strip_suffix('}')
+            .ok_or(RuleParseError::InvalidVariable)?,
+        None => s,
+    };
+
+    let mut set = Vec::new();
+    for element in elements.split(',').map(str::trim) {
+        match variables.get(element) {
+            Some(values) => set.extend(values),
+            None => set.push(parse_state(element, states)?),
+        }
+    }
+    set.sort_unstable();
+    set.dedup();
+
+    Ok(set)
+}
+
+/// An element of a transition: either a state, a set of states, or a
+/// variable.
+#[derive(Clone)]
+enum TransitionElement {
+    States(Vec<u8>),
+    Variable(String),
+}
+
+/// Parse a @TABLE section.
+fn parse_table(lines: &[&str]) -> Result<(u16, Neighbourhood, Transitions), RuleParseError> {
+    let mut states = None;
+    let mut offsets = None;
+    let mut symmetries = String::from("none");
+    let mut variables = HashMap::new();
+    let mut transitions = Vec::new();
+
+    for &line in lines {
+        if let Some(value) = parse_key_value(line, "n_states") {
+            states = Some(value
+                .parse::<u16>().ok()
+                .filter(|states| (2..=256).contains(states))
+                .ok_or(RuleParseError::InvalidStates)?);
+        } else if let Some(value) = parse_key_value(line, "neighborhood") {
+            offsets = Some(match value {
+                "Moore" => &MOORE_OFFSETS[..],
+                "vonNeumann" => &VON_NEUMANN_OFFSETS[..],
+                "hexagonal" => &HEXAGONAL_OFFSETS[..],
+                "oneDimensional" => &ONE_DIMENSIONAL_OFFSETS[..],
+                _ => return Err(RuleParseError::InvalidNeighbourhood),
+            });
+        } else if let Some(value) = parse_key_value(line, "symmetries") {
+            symmetries = value.to_owned();
+        } else if let Some(definition) = line.strip_prefix("var ") {
+            let states = states.ok_or(RuleParseError::InvalidStates)?;
+            let (name, set) = definition
+                .split_once('=')
+                .ok_or(RuleParseError::InvalidVariable)?;
+            let set = parse_state_set(set, states, &variables)?;
+            variables.insert(name.trim().to_owned(), set);
+        } else {
+            let states = states.ok_or(RuleParseError::InvalidStates)?;
+            let offsets = offsets.ok_or(RuleParseError::InvalidNeighbourhood)?;
+
+            // states may be written without commas if they are single digits
+            let elements = if line.contains(',') || line.contains('{') {
+                let mut elements = Vec::new();
+                let mut depth = 0;
+                let mut start = 0;
+                for (i, c) in line.char_indices() {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        ',' if depth == 0 => {
+                            elements.push(line[start..i].trim());
+                            start = i + 1;
+                        },
+                        _ => (),
+                    }
+                }
+                elements.push(line[start..].trim());
+                elements
+            } else {
+                line.matches(|c: char| !c.is_whitespace()).collect()
+            };
+
+            check_if(elements.len() == offsets.len() + 1, RuleParseError::InvalidTransition)?;
+
+            let elements = elements
+                .iter()
+                .map(|&element| {
+                    if variables.contains_key(element) {
+                        Ok(TransitionElement::Variable(element.to_owned()))
+                    } else if element.starts_with('{') {
+                        parse_state_set(element, states, &variables)
+                            .map(TransitionElement::States)
+                    } else {
+                        parse_state(element, states)
+                            .map(|state| TransitionElement::States(vec![state]))
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            transitions.extend(expand_transition(&elements, &variables, &symmetries)?);
+        }
+    }
+
+    let states = states.ok_or(RuleParseError::InvalidStates)?;
+    let offsets = offsets.ok_or(RuleParseError::InvalidNeighbourhood)?;
+    let neighbourhood = match offsets.len() {
+        5 => Neighbourhood::VonNeumann,
+        7 => Neighbourhood::Hexagonal,
+        _ => Neighbourhood::Moore,
+    };
+
+    Ok((
+        states,
+        neighbourhood,
+        Transitions::Table(TransitionTable::new(
+            offsets.to_vec(),
+            states as usize,
+            &transitions,
+        )),
+    ))
+}
+
+/// Expand a transition of a @TABLE to the transitions it stands for.
+///
+/// Variables appearing more than once in the transition are bound, that is
+/// all their occurrences take the same value, the others match any of their
+/// values. The transition is then expanded with all the permutations of the
+/// neighbours given by the symmetries.
+fn expand_transition(
+    elements: &[TransitionElement],
+    variables: &HashMap<String, Vec<u8>>,
+    symmetries: &str,
+) -> Result<Vec<Transition>, RuleParseError> {
+    let occurrences = |name: &str| elements
+        .iter()
+        .filter(|element| matches!(element, TransitionElement::Variable(n) if n == name))
+        .count();
+
+    let mut bound = elements
+        .iter()
+        .filter_map(|element| match element {
+            TransitionElement::Variable(name) if occurrences(name) > 1 => Some(name),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    bound.sort_unstable();
+    bound.dedup();
+
+    // enumerate the values of the bound variables
+    let mut bindings = vec![HashMap::new()];
+    for &name in &bound {
+        bindings = bindings
+            .into_iter()
+            .flat_map(|binding: HashMap<&String, u8>| {
+                variables[name].iter().map(move |&value| {
+                    let mut binding = binding.clone();
+                    binding.insert(name, value);
+                    binding
+                })
+            })
+            .collect();
+    }
+
+    let neighbours = elements.len() - 2;
+    let permutations = symmetry_permutations(symmetries, neighbours);
+    check_if(
+        permutations.is_some() || symmetries == "permute",
+        RuleParseError::InvalidSymmetries,
+    )?;
+
+    let mut transitions = Vec::new();
+    for binding in bindings {
+        let sets = elements
+            .iter()
+            .map(|element| match element {
+                TransitionElement::States(states) => states.clone(),
+                TransitionElement::Variable(name) => match binding.get(name) {
+                    Some(&value) => vec![value],
+                    None => variables[name].clone(),
+                },
+            })
+            .collect::<Vec<_>>();
+
+        let output = match &sets[neighbours + 1][..] {
+            &[output] => output,
+            _ => return Err(RuleParseError::InvalidTransition),
+        };
+        let center = sets[0].clone();
+        let outer = &sets[1..=neighbours];
+
+        let arrangements = match &permutations {
+            Some(permutations) => permutations
+                .iter()
+                .map(|permutation| {
+                    permutation.iter().map(|&i| outer[i].clone()).collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>(),
+            None => distinct_permutations(outer),
+        };
+
+        let mut seen = HashSet::new();
+        for arrangement in arrangements {
+            if seen.insert(arrangement.clone()) {
+                let mut inputs = vec![center.clone()];
+                inputs.extend(arrangement);
+                transitions.push((inputs, output));
+            }
+        }
+    }
+
+    Ok(transitions)
+}
+
+/// Parse a @TREE section.
+fn parse_tree(lines: &[&str]) -> Result<(u16, Neighbourhood, Transitions), RuleParseError> {
+    let mut states = None;
+    let mut offsets = None;
+    let mut children = Vec::new();
+    let mut levels = Vec::new();
+
+    for &line in lines {
+        if let Some(value) = parse_key_value(line, "num_states") {
+            states = Some(value
+                .parse::<u16>().ok()
+                .filter(|states| (2..=256).contains(states))
+                .ok_or(RuleParseError::InvalidStates)?);
+        } else if let Some(value) = parse_key_value(line, "num_neighbors") {
+            offsets = Some(match value {
+                "8" => &TREE_MOORE_OFFSETS[..],
+                "4" => &TREE_VON_NEUMANN_OFFSETS[..],
+                _ => return Err(RuleParseError::InvalidNeighbourhood),
+            });
+        } else if parse_key_value(line, "num_nodes").is_some() {
+            continue;
+        } else {
+            let states = states.ok_or(RuleParseError::InvalidStates)? as usize;
+            let values = line
+                .split_whitespace()
+                .map(|value| value.parse::<u32>().or(Err(RuleParseError::InvalidTree)))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            check_if(values.len() == states + 1, RuleParseError::InvalidTree)?;
+
+            let level = values[0];
+            for &child in &values[1..] {
+                if level == 1 {
+                    check_if((child as usize) < states, RuleParseError::InvalidTree)?;
+                } else {
+                    check_if(
+                        levels.get(child as usize) == Some(&(level - 1)),
+                        RuleParseError::InvalidTree,
+                    )?;
+                }
+            }
+
+            levels.push(level);
+            children.extend(&values[1..]);
+        }
+    }
+
+    let states = states.ok_or(RuleParseError::InvalidStates)?;
+    let offsets = offsets.ok_or(RuleParseError::InvalidNeighbourhood)?;
+
+    check_if(
+        levels.last() == Some(&(offsets.len() as u32)),
+        RuleParseError::InvalidTree,
+    )?;
+
+    Ok((
+        states,
+        match offsets.len() {
+            5 => Neighbourhood::VonNeumann,
+            _ => Neighbourhood::Moore,
+        },
+        Transitions::Tree(TransitionTree {
+            offsets: offsets.to_vec(),
+            states: states as usize,
+            children,
+            root: levels.len() - 1,
+        }),
+    ))
+}
+
+/// Parse a @COLORS section.
+///
+/// Each line gives either a state and its color (e.g. "1 255 0 0"), or two
+/// colors for a gradient from state 1 to the last state (e.g.
+/// "255 0 0 255 255 0").
+fn parse_colors(lines: &[&str], states: u16) -> Result<Vec<Option<String>>, RuleParseError> {
+    let mut colors = vec![None; states as usize];
+    let hex = |rgb: &[f64]| {
+        format!("#{:02x}{:02x}{:02x}", rgb[0] as u8, rgb[1] as u8, rgb[2] as u8)
+    };
+
+    for &line in lines {
+        let values = line
+            .split_whitespace()
+            .map(|value| {
+                value.parse::<u8>()
+                    .map(f64::from)
+                    .or(Err(RuleParseError::InvalidColor))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match values[..] {
+            [state, r, g, b] => {
+                let state = state as usize;
+                check_if(state < colors.len(), RuleParseError::InvalidColor)?;
+                colors[state] = Some(hex(&[r, g, b]));
+            },
+            [r0, g0, b0, r1, g1, b1] => {
+                let last = states as usize - 1;
+                for (state, color) in colors.iter_mut().enumerate().skip(1) {
+                    let t = if last > 1 {
+                        (state - 1) as f64/(last - 1) as f64
+                    } else {
+                        0.
+                    };
+                    *color = Some(hex(&[
+                        r0 + t*(r1 - r0),
+                        g0 + t*(g1 - g0),
+                        b0 + t*(b1 - b0),
+                    ]));
+                }
+            },
+            _ => return Err(RuleParseError::InvalidColor),
+        }
+    }
+
+    Ok(colors)
+}
+
+impl FromStr for CustomRule {
+    type Err = RuleParseError;
+
+    /// Parse a Golly rule file, that is a "@RULE" line giving the name of the
+    /// rule, followed by a @TABLE or a @TREE section, and an optional @COLORS
+    /// section. Other sections, like @ICONS, are ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut name = None;
+        let mut sections = HashMap::<&str, Vec<&str>>::new();
+        let mut section = "";
+
+        for line in s.lines() {
+            let line = line.split('#').next().unwrap().trim();
+
+            if let Some(rule) = line.strip_prefix("@RULE") {
+                name = Some(rule.trim().to_owned());
+                section = "@RULE";
+            } else if line.starts_with('@') {
+                section = line.split_whitespace().next().unwrap();
+            } else if !line.is_empty() {
+                sections.entry(section).or_default().push(line);
+            }
+        }
+
+        let name = name
+            .filter(|name| !name.is_empty())
+            .ok_or(RuleParseError::InvalidFormat)?;
+
+        let (states, neighbourhood, transitions) = match (
+            sections.get("@TABLE"), sections.get("@TREE"),
+        ) {
+            (Some(table), _) => parse_table(table)?,
+            (None, Some(tree)) => parse_tree(tree)?,
+            (None, None) => return Err(RuleParseError::InvalidFormat),
+        };
+
+        let colors = match sections.get("@COLORS") {
+            Some(colors) => parse_colors(colors, states)?,
+            None => vec![None; states as usize],
+        };
+
+        Ok(CustomRule {
+            name,
+            states,
+            neighbourhood,
+            transitions,
+            colors,
+        })
+    }
+}
+
+/******************************************************************************
+ * Universe
+ *****************************************************************************/
+
+#[wasm_bindgen]
+impl Universe {
+/// Load a Golly rule file and set it as the rule of the universe.
+///
+/// The rule is registered by its name, which can then be given to `set_rule`
+/// or used in the header of an RLE file. Names of built-in rules (e.g.
+/// "B3/S23" or "WireWorld") are refused, a rule may be loaded again though.
+pub fn load_rule(&mut self, content: &str) -> Result<(), RuleParseError> {
+    let rule = Rc::new(content.parse::<CustomRule>()?);
+
+    match rule.name().parse::<Rule>() {
+        Ok(Rule::Custom(loaded)) if loaded.name() == rule.name() => (),
+        Ok(_) => return Err(RuleParseError::ReservedName),
+        Err(_) => (),
+    }
+
+    CustomRule::register(rule.clone());
+    self.set_rule(rule.name())
+}}
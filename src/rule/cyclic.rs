@@ -0,0 +1,202 @@
+use std::str::FromStr;
+
+use super::*;
+use super::larger_than_life::parse_value;
+
+/******************************************************************************
+ * Rule
+ *****************************************************************************/
+
+/// A cyclic cellular automaton, or a Greenberg-Hastings excitable medium.
+///
+/// In a cyclic automaton, a cell in state k advances to state k+1 (modulo the
+/// number of states) when at least `threshold` of its neighbours within the
+/// range R are in state k+1, and keeps its state otherwise.
+///
+/// In a Greenberg-Hastings medium, a resting cell (state 0) gets excited
+/// (state 1) when at least `threshold` of its neighbours are excited, while
+/// excited and refractory cells (states 1 to N-1) always advance to the next
+/// state, back to rest after state N-1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CyclicRule {
+    range: u32,
+    threshold: u32,
+    states: u16,
+    neighbourhood: Neighbourhood,
+    greenberg_hastings: bool,
+}
+
+impl CyclicRule {
+    /// The maximum range of a rule.
+    pub const MAX_RANGE: u32 = 10;
+
+    /// Get the number of states of the rule.
+    pub fn states(&self) -> u16 {
+        self.states
+    }
+
+    /// Get the range of the rule.
+    pub fn range(&self) -> u32 {
+        self.range
+    }
+
+    /// Get the minimum number of neighbours in the next state for a cell to
+    /// advance.
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+
+    /// Get the neighbourhood of the rule.
+    pub fn neighbourhood(&self) -> Neighbourhood {
+        self.neighbourhood
+    }
+
+    /// Check if the rule is a Greenberg-Hastings rule.
+    pub fn is_greenberg_hastings(&self) -> bool {
+        self.greenberg_hastings
+    }
+
+    /// Get the default color of a state, the states being spread evenly on a
+    /// color wheel. Resting cells of Greenberg-Hastings rules are left to the
+    /// dead cells color of the theme.
+    pub fn color(&self, state: u8) -> Option<String> {
+        if self.greenberg_hastings && state == 0 {
+            return None;
+        }
+
+        let hue = 6.*state as f64/self.states as f64;
+        let channel = |offset: f64| {
+            let distance = ((hue + offset)%6. - 3.).abs();
+            (255.*(distance - 1.).clamp(0., 1.)).round() as u8
+        };
+
+        Some(format!("#{:02x}{:02x}{:02x}", channel(0.), channel(4.), channel(2.)))
+    }
+
+    /// Get the state following a state.
+    fn successor(&self, cell: Cell) -> Cell {
+        Cell::from(((cell.state() as u16 + 1)%self.states) as u8)
+    }
+
+    /// Compute the next state of the cell at (`col`, `row`) of a universe.
+    pub fn next_cell(&self, universe: &Universe, col: i32, row: i32) -> Cell {
+        let range = self.range as i32;
+        let cell = universe.cells[universe.get_index(col, row)];
+        let successor = self.successor(cell);
+
+        if self.greenberg_hastings && cell != Cell::Dead {
+            return successor;
+        }
+
+        let count = (-range..=range)
+            .map(|delta_row| {
+                let (min, max) = self.neighbourhood.row_span(range, delta_row);
+                (min..=max)
+                    .filter(|&delta_col| {
+                        (delta_col, delta_row) != (0, 0)
+                            && universe.neighbour(col + delta_col, row + delta_row) == successor
+                    })
+                    .count() as u32
+            })
+            .sum::<u32>();
+
+        if count >= self.threshold {
+            successor
+        } else {
+            cell
+        }
+    }
+
+    /// Compute the next generation of a universe.
+    pub fn next_generation(&self, universe: &Universe) -> Vec<Cell> {
+        let mut cells = universe.cells.clone();
+
+        for row in 0..universe.height {
+            for col in 0..universe.width {
+                cells[universe.get_index(col, row)] = self.next_cell(universe, col, row);
+            }
+        }
+
+        cells
+    }
+}
+
+/******************************************************************************
+ * Encoder
+ *****************************************************************************/
+
+impl std::fmt::Display for CyclicRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "R{}/T{}/C{}/N{}",
+            self.range,
+            self.threshold,
+            self.states,
+            match self.neighbourhood {
+                Neighbourhood::VonNeumann => 'N',
+                neighbourhood => neighbourhood.suffix(),
+            },
+        )?;
+        if self.greenberg_hastings {
+            write!(f, "/GH")?;
+        }
+        Ok(())
+    }
+}
+
+/******************************************************************************
+ * Decoder
+ *****************************************************************************/
+
+impl FromStr for CyclicRule {
+    type Err = RuleParseError;
+
+    /// Parse a rule in MCell's Cyclic CA notation, e.g. "R1/T3/C3/NM" for a
+    /// range of 1, a threshold of 3 and 3 states, followed by "/GH" for a
+    /// Greenberg-Hastings rule, e.g. "R1/T1/C8/NM/GH".
+    ///
+    /// The "N" part is optional, "NM" selects the Moore neighbourhood, "NN" the
+    /// von Neumann neighbourhood and "NH" the hexagonal neighbourhood.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().split('/').map(str::trim).collect::<Vec<_>>();
+
+        let greenberg_hastings = parts.last()
+            .is_some_and(|part| part.eq_ignore_ascii_case("GH"));
+        if greenberg_hastings {
+            parts.pop();
+        }
+
+        if parts.len() != 3 && parts.len() != 4 {
+            return Err(RuleParseError::InvalidFormat);
+        }
+
+        let range = parse_value(parts[0], 'R')?;
+        let threshold = parse_value(parts[1], 'T')?;
+        let states = parse_value(parts[2], 'C')?;
+        let neighbourhood = match parts.get(3).copied() {
+            Some("NM") | Some("nm") | None => Neighbourhood::Moore,
+            Some("NN") | Some("nn") => Neighbourhood::VonNeumann,
+            Some("NH") | Some("nh") => Neighbourhood::Hexagonal,
+            _ => return Err(RuleParseError::InvalidNeighbourhood),
+        };
+
+        if !(1..=Self::MAX_RANGE).contains(&range) {
+            return Err(RuleParseError::InvalidRange);
+        }
+
+        if !(2..=256).contains(&states) {
+            return Err(RuleParseError::InvalidStates);
+        }
+
+        if threshold == 0 || threshold >= neighbourhood.size(range as i32) {
+            return Err(RuleParseError::InvalidCount);
+        }
+
+        Ok(CyclicRule {
+            range,
+            threshold,
+            states: states as u16,
+            neighbourhood,
+            greenberg_hastings,
+        })
+    }
+}
@@ -0,0 +1,276 @@
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use super::*;
+
+/******************************************************************************
+ * Summed-area table
+ *****************************************************************************/
+
+/// The summed-area table of the live cells of a universe, padded with
+/// `padding` cells on each side following the topology of the universe.
+struct SummedAreaTable {
+    width: usize,
+    padding: i32,
+    sums: Vec<u32>,
+}
+
+impl SummedAreaTable {
+    fn new(universe: &Universe, padding: i32) -> Self {
+        let width = (universe.width + 2*padding) as usize + 1;
+        let height = (universe.height + 2*padding) as usize + 1;
+        let mut sums = vec![0; width*height];
+
+        for y in 1..height {
+            let mut row_sum = 0;
+            for x in 1..width {
+                let cell = universe.neighbour(
+                    x as i32 - 1 - padding,
+                    y as i32 - 1 - padding,
+                );
+                row_sum += cell.is_alive() as u32;
+                sums[y*width + x] = sums[(y - 1)*width + x] + row_sum;
+            }
+        }
+
+        SummedAreaTable { width, padding, sums }
+    }
+
+    /// Count the live cells of the rectangle of the universe going from
+    /// (`col0`, `row0`) to (`col1`, `row1`) included.
+    ///
+    /// The rectangle must not go further than `padding` cells out of the
+    /// universe.
+    fn count(&self, col0: i32, row0: i32, col1: i32, row1: i32) -> u32 {
+        let x0 = (col0 + self.padding) as usize;
+        let y0 = (row0 + self.padding) as usize;
+        let x1 = (col1 + self.padding) as usize + 1;
+        let y1 = (row1 + self.padding) as usize + 1;
+
+        self.sums[y1*self.width + x1] + self.sums[y0*self.width + x0]
+            - self.sums[y0*self.width + x1] - self.sums[y1*self.width + x0]
+    }
+}
+
+/******************************************************************************
+ * Rule
+ *****************************************************************************/
+
+/// A Larger than Life rule.
+///
+/// Cells count their live neighbours within a range R, the center cell being
+/// counted if the rule includes the middle cell. Like Generations rules, rules
+/// with more than 2 states make live cells go through the states 2 to N-1
+/// before dying.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LargerThanLifeRule {
+    range: u32,
+    states: u16,
+    middle: bool,
+    survival: (u32, u32),
+    birth: (u32, u32),
+    neighbourhood: Neighbourhood,
+}
+
+impl LargerThanLifeRule {
+    /// The maximum range of a rule.
+    pub const MAX_RANGE: u32 = 500;
+
+    /// Get the number of states of the rule.
+    pub fn states(&self) -> u16 {
+        self.states
+    }
+
+    /// Get the range of the rule.
+    pub fn range(&self) -> u32 {
+        self.range
+    }
+
+    /// Get the neighbourhood of the rule.
+    pub fn neighbourhood(&self) -> Neighbourhood {
+        self.neighbourhood
+    }
+
+    fn survival(&self) -> RangeInclusive<u32> {
+        self.survival.0..=self.survival.1
+    }
+
+    fn birth(&self) -> RangeInclusive<u32> {
+        self.birth.0..=self.birth.1
+    }
+
+    /// Compute the next state of a cell given its number of live neighbours,
+    /// center cell included if the rule includes the middle cell.
+    pub fn next_state(&self, cell: Cell, count: u32) -> Cell {
+        match cell {
+            Cell::Dead if self.birth().contains(&count) => Cell::Alive,
+            Cell::Alive if self.survival().contains(&count) => Cell::Alive,
+            _ => decay(cell, self.states),
+        }
+    }
+
+    /// Compute the next state of the cell at (`col`, `row`) of a universe,
+    /// counting its neighbours directly.
+    pub fn next_cell(&self, universe: &Universe, col: i32, row: i32) -> Cell {
+        let range = self.range as i32;
+        let cell = universe.cells[universe.get_index(col, row)];
+
+        let mut count = (-range..=range)
+            .map(|delta_row| {
+                let (min, max) = self.neighbourhood.row_span(range, delta_row);
+                (min..=max)
+                    .filter(|&delta_col| {
+                        universe.neighbour(col + delta_col, row + delta_row).is_alive()
+                    })
+                    .count() as u32
+            })
+            .sum::<u32>();
+
+        if !self.middle && cell.is_alive() {
+            count -= 1;
+        }
+
+        self.next_state(cell, count)
+    }
+
+    /// Compute the next generation of a universe.
+    ///
+    /// The neighbourhood counts are computed with a summed-area table, each
+    /// row of the neighbourhood costing a constant time whatever the range.
+    pub fn next_generation(&self, universe: &Universe) -> Vec<Cell> {
+        let range = self.range as i32;
+        let table = SummedAreaTable::new(universe, range);
+        let mut cells = universe.cells.clone();
+
+        for row in 0..universe.height {
+            for col in 0..universe.width {
+                let idx = universe.get_index(col, row);
+                let cell = universe.cells[idx];
+
+                let mut count = match self.neighbourhood {
+                    Neighbourhood::Moore => {
+                        table.count(col - range, row - range, col + range, row + range)
+                    },
+                    _ => (-range..=range)
+                        .map(|delta_row| {
+                            let (min, max) = self.neighbourhood.row_span(range, delta_row);
+                            table.count(
+                                col + min, row + delta_row,
+                                col + max, row + delta_row,
+                            )
+                        })
+                        .sum(),
+                };
+
+                if !self.middle && cell.is_alive() {
+                    count -= 1;
+                }
+
+                cells[idx] = self.next_state(cell, count);
+            }
+        }
+
+        cells
+    }
+}
+
+/******************************************************************************
+ * Encoder
+ *****************************************************************************/
+
+impl std::fmt::Display for LargerThanLifeRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "R{},C{},M{},S{}..{},B{}..{},N{}",
+            self.range,
+            if self.states > 2 { self.states } else { 0 },
+            self.middle as u8,
+            self.survival.0, self.survival.1,
+            self.birth.0, self.birth.1,
+            match self.neighbourhood {
+                Neighbourhood::VonNeumann => 'N',
+                neighbourhood => neighbourhood.suffix(),
+            },
+        )
+    }
+}
+
+/******************************************************************************
+ * Decoder
+ *****************************************************************************/
+
+pub(super) fn parse_value(s: &str, prefix: char) -> Result<u32, RuleParseError> {
+    s.strip_prefix([prefix, prefix.to_ascii_lowercase()])
+        .ok_or(RuleParseError::InvalidFormat)?
+        .parse::<u32>()
+        .or(Err(RuleParseError::InvalidFormat))
+}
+
+fn parse_range(s: &str, prefix: char) -> Result<(u32, u32), RuleParseError> {
+    let range = s
+        .strip_prefix([prefix, prefix.to_ascii_lowercase()])
+        .ok_or(RuleParseError::InvalidFormat)?
+        .split("..")
+        .map(|bound| bound.parse::<u32>().or(Err(RuleParseError::InvalidFormat)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match range[..] {
+        [min, max] if min <= max => Ok((min, max)),
+        [_, _] => Err(RuleParseError::InvalidRange),
+        _ => Err(RuleParseError::InvalidFormat),
+    }
+}
+
+impl FromStr for LargerThanLifeRule {
+    type Err = RuleParseError;
+
+    /// Parse a rule in Golly's Larger than Life notation, e.g.
+    /// "R5,C0,M1,S34..58,B34..45,NM".
+    ///
+    /// The "N" part is optional, "NM" selects the Moore neighbourhood, "NN" the
+    /// von Neumann neighbourhood and "NH" the hexagonal neighbourhood.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s.trim().split(',').map(str::trim).collect::<Vec<_>>();
+
+        if parts.len() != 5 && parts.len() != 6 {
+            return Err(RuleParseError::InvalidFormat);
+        }
+
+        let range = parse_value(parts[0], 'R')?;
+        let states = parse_value(parts[1], 'C')?;
+        let middle = parse_value(parts[2], 'M')?;
+        let survival = parse_range(parts[3], 'S')?;
+        let birth = parse_range(parts[4], 'B')?;
+        let neighbourhood = match parts.get(5).copied() {
+            Some("NM") | Some("nm") | None => Neighbourhood::Moore,
+            Some("NN") | Some("nn") => Neighbourhood::VonNeumann,
+            Some("NH") | Some("nh") => Neighbourhood::Hexagonal,
+            _ => return Err(RuleParseError::InvalidNeighbourhood),
+        };
+
+        if !(1..=Self::MAX_RANGE).contains(&range) {
+            return Err(RuleParseError::InvalidRange);
+        }
+
+        if states > 256 {
+            return Err(RuleParseError::InvalidStates);
+        }
+
+        if middle > 1 {
+            return Err(RuleParseError::InvalidFormat);
+        }
+
+        let size = neighbourhood.size(range as i32);
+        if survival.1 > size || birth.1 > size {
+            return Err(RuleParseError::InvalidCount);
+        }
+
+        Ok(LargerThanLifeRule {
+            range,
+            states: (states as u16).max(2),
+            middle: middle == 1,
+            survival,
+            birth,
+            neighbourhood,
+        })
+    }
+}
@@ -0,0 +1,718 @@
+use std::str::FromStr;
+
+use super::*;
+
+/******************************************************************************
+ * Neighbourhood
+ *****************************************************************************/
+
+/// Bit of the center cell in a neighbourhood index.
+///
+/// A neighbourhood index packs the 9 cells of a 3x3 neighbourhood, in reading
+/// order, from the most significant bit (north-west cell) down to the least
+/// significant bit (south-east cell).
+pub const NEIGHBOURHOOD_CENTER: u16 = 1 << 4;
+
+/// Mask of the 8 neighbours of the center cell in a neighbourhood index.
+const NEIGHBOURHOOD_OUTER: u16 = 0x1ff & !NEIGHBOURHOOD_CENTER;
+
+/// Get the bit of the cell at the given offset from the center cell in a
+/// neighbourhood index.
+pub fn neighbourhood_bit(delta_col: i32, delta_row: i32) -> u16 {
+    1 << (8 - (3*(delta_row + 1) + delta_col + 1))
+}
+
+fn transform_neighbourhood(
+    neighbourhood: u16,
+    f: impl Fn(i32, i32) -> (i32, i32),
+) -> u16 {
+    (-1..=1)
+        .flat_map(|delta_row| (-1..=1).map(move |delta_col| (delta_col, delta_row)))
+        .filter(|&(delta_col, delta_row)| {
+            neighbourhood & neighbourhood_bit(delta_col, delta_row) != 0
+        })
+        .fold(0, |acc, (delta_col, delta_row)| {
+            let (delta_col, delta_row) = f(delta_col, delta_row);
+            acc | neighbourhood_bit(delta_col, delta_row)
+        })
+}
+
+/// Get the 8 rotations and reflections of a neighbourhood index.
+fn neighbourhood_symmetries(neighbourhood: u16) -> Vec<u16> {
+    let mut symmetries = Vec::with_capacity(8);
+    let mut current = neighbourhood;
+
+    for _ in 0..4 {
+        symmetries.push(current);
+        symmetries.push(transform_neighbourhood(current, |x, y| (-x, y)));
+        current = transform_neighbourhood(current, |x, y| (-y, x));
+    }
+
+    symmetries
+}
+
+/******************************************************************************
+ * Hensel notation
+ *****************************************************************************/
+
+/// Letters of the isotropic configurations of 0 to 4 neighbours.
+///
+/// The configurations of 5 to 8 neighbours use the letters of the complement
+/// configuration of 3 to 0 neighbours.
+const HENSEL_LETTERS: [&str; 5] = [
+    "",
+    "ce",
+    "ceaikn",
+    "ceaiknjqry",
+    "ceaiknjqrtwyz",
+];
+
+/// A representative neighbourhood of each letter of `HENSEL_LETTERS`.
+const HENSEL_NEIGHBOURHOODS: [&[u16]; 5] = [
+    &[0],
+    &[1, 2],
+    &[5, 10, 3, 40, 33, 68],
+    &[69, 42, 11, 7, 98, 13, 14, 70, 41, 97],
+    &[325, 170, 15, 45, 99, 71, 106, 102, 43, 101, 105, 78, 108],
+];
+
+fn hensel_letters(count: u8) -> &'static str {
+    HENSEL_LETTERS[count.min(8 - count) as usize]
+}
+
+/// Get all the neighbourhoods of the isotropic configuration given by a
+/// neighbour count and a letter of the Hensel notation.
+fn hensel_neighbourhoods(count: u8, letter: char) -> Vec<u16> {
+    let index = hensel_letters(count).find(letter).unwrap();
+    let neighbourhood = if count <= 4 {
+        HENSEL_NEIGHBOURHOODS[count as usize][index]
+    } else {
+        NEIGHBOURHOOD_OUTER & !HENSEL_NEIGHBOURHOODS[8 - count as usize][index]
+    };
+
+    neighbourhood_symmetries(neighbourhood)
+}
+
+/// Get the mask of the neighbours of the center cell in a neighbourhood index.
+fn neighbours_mask(neighbourhood: Neighbourhood) -> u16 {
+    (-1..=1)
+        .flat_map(|delta_row| {
+            let (min, max) = neighbourhood.row_span(1, delta_row);
+            (min..=max).map(move |delta_col| (delta_col, delta_row))
+        })
+        .fold(0, |mask, (delta_col, delta_row)| {
+            mask | neighbourhood_bit(delta_col, delta_row)
+        }) & NEIGHBOURHOOD_OUTER
+}
+
+/// Get all the neighbourhoods with the given number of live neighbours, the
+/// cells out of the neighbourhood being in any state.
+fn count_neighbourhoods(count: u8, neighbourhood: Neighbourhood) -> Vec<u16> {
+    let mask = neighbours_mask(neighbourhood);
+    (0..0x200)
+        .filter(|index| index & NEIGHBOURHOOD_CENTER == 0)
+        .filter(|index: &u16| (index & mask).count_ones() == count as u32)
+        .collect()
+}
+
+/******************************************************************************
+ * MAP notation
+ *****************************************************************************/
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Number of base64 digits of a MAP rule table, without padding.
+const MAP_LENGTH: usize = 86;
+
+fn base64_digit(c: char) -> Option<u64> {
+    BASE64_ALPHABET
+        .iter()
+        .position(|&digit| digit as char == c)
+        .map(|digit| digit as u64)
+}
+
+/******************************************************************************
+ * Rule
+ *****************************************************************************/
+
+/// A Life-like rule, possibly with Generations states.
+///
+/// The rule is stored as a 512 entries transition table, indexed by the
+/// neighbourhood index of a cell (see `NEIGHBOURHOOD_CENTER`). An entry is set
+/// when a cell with that neighbourhood is alive at the next generation. Rules
+/// on the von Neumann or hexagonal neighbourhoods simply ignore the cells out
+/// of their neighbourhood, and rules given in MAP notation may be anisotropic.
+///
+/// Generations rules have more than 2 states: a live cell which does not
+/// survive goes through the states 2 to N-1 before dying. Only cells in
+/// state 1 count as live cells in a neighbourhood.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LifeLikeRule {
+    table: [u64; 8],
+    states: u16,
+    neighbourhood: Neighbourhood,
+}
+
+impl Default for LifeLikeRule {
+    /// Conway's Game of Life, B3/S23.
+    fn default() -> Self {
+        LifeLikeRule::new(&[3], &[2, 3])
+    }
+}
+
+impl LifeLikeRule {
+    /// Create an outer totalistic rule from its birth and survival neighbour
+    /// counts.
+    ///
+    /// Counts greater than 8 are ignored.
+    pub fn new(birth: &[u8], survival: &[u8]) -> Self {
+        LifeLikeRule::with_neighbourhood(birth, survival, Neighbourhood::Moore)
+    }
+
+    /// Create an outer totalistic rule on the given neighbourhood from its
+    /// birth and survival neighbour counts.
+    ///
+    /// Counts greater than the number of neighbours are ignored.
+    pub fn with_neighbourhood(
+        birth: &[u8],
+        survival: &[u8],
+        neighbourhood: Neighbourhood,
+    ) -> Self {
+        let mut rule = LifeLikeRule {
+            table: [0; 8],
+            states: 2,
+            neighbourhood,
+        };
+
+        for &count in birth {
+            for index in count_neighbourhoods(count, neighbourhood) {
+                rule.set_entry(index, true);
+            }
+        }
+
+        for &count in survival {
+            for index in count_neighbourhoods(count, neighbourhood) {
+                rule.set_entry(index | NEIGHBOURHOOD_CENTER, true);
+            }
+        }
+
+        rule
+    }
+
+    /// Create a Generations rule from its birth and survival neighbour counts
+    /// and its number of states.
+    ///
+    /// The number of states is clamped to the range 2 to 256.
+    pub fn with_states(birth: &[u8], survival: &[u8], states: u16) -> Self {
+        LifeLikeRule {
+            states: states.clamp(2, 256),
+            ..LifeLikeRule::new(birth, survival)
+        }
+    }
+
+    /// Get the number of states of the rule.
+    pub fn states(&self) -> u16 {
+        self.states
+    }
+
+    /// Get the neighbourhood of the rule.
+    pub fn neighbourhood(&self) -> Neighbourhood {
+        self.neighbourhood
+    }
+
+    /// Check if the rule is isotropic, that is if it is invariant under the
+    /// rotations and reflections of the neighbourhood.
+    pub fn is_isotropic(&self) -> bool {
+        (0..0x200).all(|index| {
+            neighbourhood_symmetries(index)
+                .iter()
+                .all(|&symmetry| self.entry(symmetry) == self.entry(index))
+        })
+    }
+
+    /// Check if the rule is totalistic, that is if the next state of a cell
+    /// only depends on its state and on its number of live neighbours.
+    pub fn is_totalistic(&self) -> bool {
+        [0, NEIGHBOURHOOD_CENTER].iter().all(|&center| {
+            (0..=8).all(|count| {
+                count_neighbourhoods(count, self.neighbourhood)
+                    .windows(2)
+                    .all(|pair| self.entry(pair[0] | center) == self.entry(pair[1] | center))
+            })
+        })
+    }
+
+    /// Get the entry of the transition table for a neighbourhood index.
+    pub fn entry(&self, neighbourhood: u16) -> bool {
+        let neighbourhood = (neighbourhood & 0x1ff) as usize;
+        self.table[neighbourhood/64] & (1 << (neighbourhood%64)) != 0
+    }
+
+    fn set_entry(&mut self, neighbourhood: u16, value: bool) {
+        let neighbourhood = (neighbourhood & 0x1ff) as usize;
+        if value {
+            self.table[neighbourhood/64] |= 1 << (neighbourhood%64);
+        } else {
+            self.table[neighbourhood/64] &= !(1 << (neighbourhood%64));
+        }
+    }
+
+    /// Compute the next state of a cell given its neighbourhood index.
+    pub fn next_state(&self, cell: Cell, neighbourhood: u16) -> Cell {
+        match cell {
+            Cell::Dead | Cell::Alive if self.entry(neighbourhood) => {
+                Cell::Alive
+            },
+            Cell::Dead => Cell::Dead,
+            _ => decay(cell, self.states),
+        }
+    }
+
+    /// Get the next state of the background of a universe given its current
+    /// state.
+    ///
+    /// The background of a 2 states rule containing B0 turns on, and it then
+    /// stays on if the rule contains S8 too, or it turns off. Generations
+    /// rules always have a dead background.
+    pub fn next_background(&self, background: Cell) -> Cell {
+        let neighbourhood = match background {
+            Cell::Alive => 0x1ff,
+            _ => 0,
+        };
+
+        if self.states == 2 && self.entry(neighbourhood) {
+            Cell::Alive
+        } else {
+            Cell::Dead
+        }
+    }
+
+    /// Compute the actual next state of the cell at (`col`, `row`) of a
+    /// universe, the cells of the universe being stored relative to its
+    /// background (see `Universe::background`).
+    pub fn next_cell(&self, universe: &Universe, col: i32, row: i32) -> Cell {
+        let background = universe.background;
+        let mask = match background {
+            Cell::Alive => 0x1ff,
+            _ => 0,
+        };
+        let cell = universe.cells[universe.get_index(col, row)];
+        let cell = Cell::from(cell.state() ^ background.state());
+
+        self.next_state(cell, universe.neighbourhood(col, row) ^ mask)
+    }
+
+    /// Compute the next generation of a universe.
+    ///
+    /// The next generation is stored relative to the next background of the
+    /// universe.
+    pub fn next_generation(&self, universe: &Universe) -> Vec<Cell> {
+        let next_background = self.next_background(universe.background);
+
+        let mut cells = universe.cells.clone();
+
+        for row in 0..universe.height {
+            for col in 0..universe.width {
+                let next_cell = self.next_cell(universe, col, row);
+                cells[universe.get_index(col, row)] =
+                    Cell::from(next_cell.state() ^ next_background.state());
+            }
+        }
+
+        cells
+    }
+
+    /// Get the birth and the survival neighbour counts of an outer totalistic
+    /// rule on the Moore neighbourhood, as bit masks (see
+    /// `Universe::packed_step`).
+    pub(crate) fn moore_counts(&self) -> Option<(u16, u16)> {
+        if self.neighbourhood != Neighbourhood::Moore || !self.is_totalistic() {
+            return None;
+        }
+
+        Some((0..=8).fold((0, 0), |(birth, survival), count| {
+            // the `count` first neighbours alive
+            let index = (0..count).fold(NEIGHBOURHOOD_OUTER, |index, _| index & (index - 1))
+                ^ NEIGHBOURHOOD_OUTER;
+            (
+                birth | (self.entry(index) as u16) << count,
+                survival | (self.entry(index | NEIGHBOURHOOD_CENTER) as u16) << count,
+            )
+        }))
+    }
+}
+
+/******************************************************************************
+ * Encoder
+ *****************************************************************************/
+
+impl LifeLikeRule {
+    /// Write the conditions of a birth (center is 0) or a survival (center is
+    /// `NEIGHBOURHOOD_CENTER`) in Hensel notation.
+    ///
+    /// Counts for which only some configurations match are written with the
+    /// shortest list of letters, either the matching ones, or the non matching
+    /// ones after a "-".
+    fn hensel_conditions(&self, center: u16) -> String {
+        let mut conditions = String::new();
+
+        for count in 0..=8 {
+            let (matching, non_matching): (Vec<char>, Vec<char>) =
+                hensel_letters(count)
+                    .chars()
+                    .partition(|&letter| {
+                        self.entry(hensel_neighbourhoods(count, letter)[0] | center)
+                    });
+
+            let all = count_neighbourhoods(count, Neighbourhood::Moore)
+                .iter()
+                .all(|&index| self.entry(index | center));
+            let none = count_neighbourhoods(count, Neighbourhood::Moore)
+                .iter()
+                .all(|&index| !self.entry(index | center));
+
+            if all {
+                conditions.push_str(&count.to_string());
+            } else if !none {
+                conditions.push_str(&count.to_string());
+                if matching.len() <= non_matching.len() {
+                    conditions.extend(matching);
+                } else {
+                    conditions.push('-');
+                    conditions.extend(non_matching);
+                }
+            }
+        }
+
+        conditions
+    }
+
+    /// Write the conditions of a birth (center is 0) or a survival (center is
+    /// `NEIGHBOURHOOD_CENTER`) as a list of neighbour counts.
+    fn totalistic_conditions(&self, center: u16) -> String {
+        let mask = neighbours_mask(self.neighbourhood);
+
+        (0..=mask.count_ones())
+            .filter(|&count| {
+                // the `count` first neighbours alive
+                let index = (0..count).fold(mask, |index, _| index & (index - 1)) ^ mask;
+                self.entry(index | center)
+            })
+            .map(|count| count.to_string())
+            .collect()
+    }
+
+    /// Write the rule in B/S notation (e.g. "B36/S23", "B2-a/S12",
+    /// "B2/S/C3" or "B2/S34H"), if the rule is isotropic.
+    pub fn to_bs(&self) -> Option<String> {
+        let mut rule = match self.neighbourhood {
+            Neighbourhood::Moore if !self.is_isotropic() => return None,
+            Neighbourhood::Moore => format!("B{}/S{}",
+                self.hensel_conditions(0),
+                self.hensel_conditions(NEIGHBOURHOOD_CENTER),
+            ),
+            _ => format!("B{}/S{}",
+                self.totalistic_conditions(0),
+                self.totalistic_conditions(NEIGHBOURHOOD_CENTER),
+            ),
+        };
+
+        if self.states > 2 {
+            rule.push_str(&format!("/C{}", self.states));
+        }
+
+        if self.neighbourhood != Neighbourhood::Moore {
+            rule.push(self.neighbourhood.suffix());
+        }
+
+        Some(rule)
+    }
+
+    /// Write the rule in S/B notation (e.g. "23/36" or "345/2/4"), if the
+    /// rule is totalistic.
+    pub fn to_sb(&self) -> Option<String> {
+        if !self.is_totalistic() {
+            return None;
+        }
+
+        let mut rule = format!("{}/{}",
+            self.totalistic_conditions(NEIGHBOURHOOD_CENTER),
+            self.totalistic_conditions(0),
+        );
+
+        if self.states > 2 {
+            rule.push_str(&format!("/{}", self.states));
+        }
+
+        if self.neighbourhood != Neighbourhood::Moore {
+            rule.push(self.neighbourhood.suffix());
+        }
+
+        Some(rule)
+    }
+
+    /// Get the Wolfram code of the rule, if the rule is a 2 states totalistic
+    /// rule with the Moore neighbourhood.
+    ///
+    /// Bit 2n of the code is set when cells are born with n live neighbours,
+    /// and bit 2n + 1 when cells survive with n live neighbours, so that
+    /// Conway's Game of Life is the code 224.
+    pub fn to_wolfram(&self) -> Option<u32> {
+        if self.states != 2 || self.neighbourhood != Neighbourhood::Moore || !self.is_totalistic() {
+            return None;
+        }
+
+        Some((0..=8).fold(0, |code, count| {
+            let index = count_neighbourhoods(count, Neighbourhood::Moore)[0];
+            code
+                | (self.entry(index) as u32) << (2*count)
+                | (self.entry(index | NEIGHBOURHOOD_CENTER) as u32) << (2*count + 1)
+        }))
+    }
+
+    /// Write the rule in MAP notation, that is "MAP" followed by the 512
+    /// entries of its transition table encoded in base64, without padding.
+    ///
+    /// Generations rules are followed by their number of states (e.g.
+    /// "MAP.../C3").
+    pub fn to_map(&self) -> String {
+        // 512 bits are 85 full base64 digits of 6 bits, and 2 bits left
+        let mut map = String::from("MAP");
+
+        for digit in 0..MAP_LENGTH {
+            let value = (0..6).fold(0, |value, bit| {
+                let index = (6*digit + bit) as u16;
+                2*value + (index < 0x200 && self.entry(index)) as usize
+            });
+            map.push(BASE64_ALPHABET[value] as char);
+        }
+
+        if self.states > 2 {
+            map.push_str(&format!("/C{}", self.states));
+        }
+
+        map
+    }
+}
+
+impl std::fmt::Display for LifeLikeRule {
+    /// Write the rule in B/S notation, or in MAP notation if the rule is not
+    /// isotropic.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.to_bs() {
+            Some(rule) => write!(f, "{}", rule),
+            None => write!(f, "{}", self.to_map()),
+        }
+    }
+}
+
+/******************************************************************************
+ * Decoder
+ *****************************************************************************/
+
+/// Parse birth or survival conditions in Hensel notation (e.g. "2-a3cnqy"),
+/// and get the matching neighbourhood indices, without the center cell.
+///
+/// Hensel notation letters are only allowed with the Moore neighbourhood.
+fn parse_conditions(
+    s: &str,
+    neighbourhood: Neighbourhood,
+) -> Result<Vec<u16>, RuleParseError> {
+    let mut neighbourhoods = Vec::new();
+    let mut chars = s.chars().peekable();
+    let max_count = neighbours_mask(neighbourhood).count_ones();
+
+    while let Some(c) = chars.next() {
+        let count = match c.to_digit(10) {
+            Some(count) if count <= max_count => count as u8,
+            _ => return Err(RuleParseError::InvalidCount),
+        };
+
+        let negated = chars.next_if_eq(&'-').is_some();
+        let mut letters = String::new();
+        while let Some(letter) = chars.next_if(char::is_ascii_lowercase) {
+            if neighbourhood != Neighbourhood::Moore
+                || !hensel_letters(count).contains(letter) {
+                return Err(RuleParseError::InvalidLetter);
+            }
+            letters.push(letter);
+        }
+
+        if negated && letters.is_empty() {
+            return Err(RuleParseError::InvalidFormat);
+        }
+
+        if letters.is_empty() {
+            neighbourhoods.extend(count_neighbourhoods(count, neighbourhood));
+        } else {
+            hensel_letters(count)
+                .chars()
+                .filter(|&letter| letters.contains(letter) != negated)
+                .for_each(|letter| {
+                    neighbourhoods.extend(hensel_neighbourhoods(count, letter));
+                });
+        }
+    }
+
+    Ok(neighbourhoods)
+}
+
+/// Parse the number of states of a Generations rule, with or without a "C"
+/// prefix.
+fn parse_states(s: &str) -> Result<u16, RuleParseError> {
+    s.strip_prefix(['C', 'c'])
+        .unwrap_or(s)
+        .parse::<u16>().ok()
+        .filter(|states| (2..=256).contains(states))
+        .ok_or(RuleParseError::InvalidStates)
+}
+
+impl LifeLikeRule {
+    /// Parse a rule in MAP notation, that is "MAP" followed by the 512
+    /// entries of its transition table encoded in base64, with or without
+    /// padding, and optionally followed by a number of states (e.g.
+    /// "MAP.../C3" or "MAP.../3").
+    pub fn from_map(s: &str) -> Result<Self, RuleParseError> {
+        let s = s.trim();
+        let s = s
+            .strip_prefix("MAP")
+            .or_else(|| s.strip_prefix("map"))
+            .ok_or(RuleParseError::InvalidFormat)?;
+        let (map, states) = match s.split_once('/') {
+            Some((map, states)) => (map, parse_states(states)?),
+            None => (s, 2),
+        };
+        let map = map.strip_suffix("==").unwrap_or(map);
+
+        if map.chars().count() != MAP_LENGTH {
+            return Err(RuleParseError::InvalidFormat);
+        }
+
+        let mut rule = LifeLikeRule {
+            table: [0; 8],
+            states,
+            neighbourhood: Neighbourhood::Moore,
+        };
+
+        for (digit, c) in map.chars().enumerate() {
+            let value = base64_digit(c).ok_or(RuleParseError::InvalidFormat)?;
+            for bit in 0..6 {
+                let index = (6*digit + bit) as u16;
+                if index < 0x200 {
+                    rule.set_entry(index, value & (1 << (5 - bit)) != 0);
+                }
+            }
+        }
+
+        Ok(rule)
+    }
+
+    /// Create a 2 states totalistic rule with the Moore neighbourhood from its
+    /// Wolfram code (see `to_wolfram`).
+    pub fn from_wolfram(code: u32) -> Result<Self, RuleParseError> {
+        if code >= 1 << 18 {
+            return Err(RuleParseError::InvalidFormat);
+        }
+
+        let counts = |bit: u32| (0..=8u8)
+            .filter(|&count| code & (1 << (2*count as u32 + bit)) != 0)
+            .collect::<Vec<u8>>();
+
+        Ok(LifeLikeRule::new(&counts(0), &counts(1)))
+    }
+}
+
+impl FromStr for LifeLikeRule {
+    type Err = RuleParseError;
+
+    /// Parse a rule in B/S notation (e.g. "B36/S23"), or in the older S/B
+    /// notation (e.g. "23/36"). The "B" and "S" prefixes are case insensitive
+    /// and can be given in any order.
+    ///
+    /// Each neighbour count may be followed by letters of the Hensel notation
+    /// to select some of its isotropic configurations (e.g. "B2-a/S12" or
+    /// "B3/S2-i34q").
+    ///
+    /// The "/" separating the birth and the survival conditions may be
+    /// omitted in B/S notation (e.g. "b3s23").
+    ///
+    /// Generations rules have a third part giving the number of states, with
+    /// or without a "C" prefix (e.g. "B2/S/C3" or "345/2/4").
+    ///
+    /// A "V" or "H" suffix selects the von Neumann or the hexagonal
+    /// neighbourhood (e.g. "B2/S013V" or "B2/S34H").
+    ///
+    /// Rules in MAP notation (see `LifeLikeRule::from_map`) and Wolfram codes
+    /// prefixed with "C" (e.g. "C224", see `LifeLikeRule::from_wolfram`) are
+    /// accepted too. Wolfram codes need their prefix, as "W" is the prefix of
+    /// one-dimensional rules, and digits alone would be mistaken for the S/B
+    /// notation.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.starts_with("MAP") || s.starts_with("map") {
+            return LifeLikeRule::from_map(s);
+        }
+
+        if let Some(code) = s.strip_prefix(['C', 'c']) {
+            if code.is_empty() || !code.bytes().all(|c| c.is_ascii_digit()) {
+                return Err(RuleParseError::InvalidFormat);
+            }
+            let code = code.parse().or(Err(RuleParseError::InvalidFormat))?;
+            return LifeLikeRule::from_wolfram(code);
+        }
+
+        let (s, neighbourhood) = match s.chars().last() {
+            Some('V') | Some('v') => (&s[..s.len() - 1], Neighbourhood::VonNeumann),
+            Some('H') | Some('h') => (&s[..s.len() - 1], Neighbourhood::Hexagonal),
+            _ => (s, Neighbourhood::Moore),
+        };
+        let parts = match s.char_indices().skip(1).find(|&(_, c)| "BbSs".contains(c)) {
+            Some((i, _)) if !s.contains('/') => vec![&s[..i], &s[i..]],
+            _ => s.split('/').collect::<Vec<_>>(),
+        };
+
+        if parts.len() != 2 && parts.len() != 3 {
+            return Err(RuleParseError::InvalidFormat);
+        }
+
+        let states = match parts.get(2) {
+            Some(part) => parse_states(part)?,
+            None => 2,
+        };
+
+        let prefixed = |part: &str, prefix: char| part
+            .strip_prefix(prefix)
+            .or_else(|| part.strip_prefix(prefix.to_ascii_lowercase()))
+            .map(str::to_owned);
+
+        let (birth, survival) = match (
+            prefixed(parts[0], 'B'), prefixed(parts[1], 'S'),
+            prefixed(parts[0], 'S'), prefixed(parts[1], 'B'),
+        ) {
+            (Some(birth), Some(survival), _, _) => (birth, survival),
+            (_, _, Some(survival), Some(birth)) => (birth, survival),
+            (None, None, None, None) => {
+                // S/B notation
+                (parts[1].to_owned(), parts[0].to_owned())
+            },
+            _ => return Err(RuleParseError::InvalidFormat),
+        };
+
+        let mut rule = LifeLikeRule {
+            table: [0; 8],
+            states,
+            neighbourhood,
+        };
+
+        for index in parse_conditions(&birth, neighbourhood)? {
+            rule.set_entry(index, true);
+        }
+
+        for index in parse_conditions(&survival, neighbourhood)? {
+            rule.set_entry(index | NEIGHBOURHOOD_CENTER, true);
+        }
+
+        Ok(rule)
+    }
+}
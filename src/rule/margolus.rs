@@ -0,0 +1,159 @@
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+use super::*;
+
+/******************************************************************************
+ * Rule
+ *****************************************************************************/
+
+/// A Margolus block cellular automaton rule.
+///
+/// The universe is partitioned into 2x2 blocks, the partition being shifted by
+/// one cell down and right on odd generations. Each block is replaced as a
+/// whole according to a permutation of the 16 possible blocks, a block being
+/// indexed by its live cells, the upper-left cell having the weight 1, the
+/// upper-right cell 2, the lower-left cell 4 and the lower-right cell 8.
+///
+/// Blocks wrap around the edges of the universe, which must therefore have an
+/// even width and height so that blocks never overlap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MargolusRule {
+    blocks: [u8; 16],
+}
+
+impl MargolusRule {
+    /// Create a rule from its block permutation, or return `None` if `blocks`
+    /// is not a permutation of the blocks, that is if the rule is not
+    /// reversible.
+    pub fn new(blocks: [u8; 16]) -> Option<Self> {
+        let mut seen = 0u16;
+
+        for &block in blocks.iter() {
+            if block > 15 {
+                return None;
+            }
+            seen |= 1 << block;
+        }
+
+        if seen == u16::MAX {
+            Some(MargolusRule { blocks })
+        } else {
+            None
+        }
+    }
+
+    /// Get the number of states of the rule.
+    pub fn states(&self) -> u16 {
+        2
+    }
+
+    /// Get the block replacing a block.
+    pub fn next_block(&self, block: u8) -> u8 {
+        self.blocks[block as usize & 15]
+    }
+
+    /// Compute the next state of the cell at (`col`, `row`) of a universe,
+    /// given by the next block of the block it belongs to.
+    pub fn next_cell(&self, universe: &Universe, col: i32, row: i32) -> Cell {
+        let offset = (universe.generation%2) as i32;
+        let left = col - (col - offset).rem_euclid(2);
+        let top = row - (row - offset).rem_euclid(2);
+
+        let block = [(0, 0), (1, 0), (0, 1), (1, 1)]
+            .iter()
+            .enumerate()
+            .fold(0, |block, (bit, &(delta_col, delta_row))| {
+                let cell = universe.neighbour(left + delta_col, top + delta_row);
+                block | ((cell.is_alive() as u8) << bit)
+            });
+        let bit = (col - left) + 2*(row - top);
+
+        Cell::from((self.next_block(block) >> bit) & 1)
+    }
+
+    /// Compute the next generation of a universe.
+    ///
+    /// The partition phase is given by the parity of the generation of the
+    /// universe.
+    pub fn next_generation(&self, universe: &Universe) -> Vec<Cell> {
+        let offset = (universe.generation%2) as i32;
+        let mut cells = universe.cells.clone();
+
+        for row in (offset..universe.height + offset).step_by(2) {
+            for col in (offset..universe.width + offset).step_by(2) {
+                // cells of blocks beyond the edges of a bounded universe are
+                // dead, and their next state is dropped
+                let indices = [
+                    universe.neighbour_index(col, row),
+                    universe.neighbour_index(col + 1, row),
+                    universe.neighbour_index(col, row + 1),
+                    universe.neighbour_index(col + 1, row + 1),
+                ];
+
+                let block = indices
+                    .iter()
+                    .enumerate()
+                    .fold(0, |block, (bit, &idx)| {
+                        let alive = idx.is_some_and(|idx| universe.cells[idx].is_alive());
+                        block | ((alive as u8) << bit)
+                    });
+                let next_block = self.next_block(block);
+
+                for (bit, &idx) in indices.iter().enumerate() {
+                    if let Some(idx) = idx {
+                        cells[idx] = Cell::from((next_block >> bit) & 1);
+                    }
+                }
+            }
+        }
+
+        cells
+    }
+}
+
+/******************************************************************************
+ * Encoder
+ *****************************************************************************/
+
+impl std::fmt::Display for MargolusRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let blocks = self.blocks
+            .iter()
+            .map(|block| block.to_string())
+            .collect::<Vec<_>>();
+        write!(f, "M{}", blocks.join(","))
+    }
+}
+
+/******************************************************************************
+ * Decoder
+ *****************************************************************************/
+
+impl FromStr for MargolusRule {
+    type Err = RuleParseError;
+
+    /// Parse a rule in Golly's Margolus notation, that is "M" followed by the
+    /// 16 blocks of the permutation, e.g. "M0,8,4,3,2,5,9,7,1,6,10,11,12,13,14,15"
+    /// for the Billiard Ball Machine.
+    ///
+    /// MCell's "MS,D" prefix and semicolons separators (e.g.
+    /// "MS,D15;14;13;3;11;5;6;1;7;9;10;2;12;4;8;0" for Critters) are accepted
+    /// too.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let blocks = s
+            .strip_prefix("MS,D")
+            .or_else(|| s.strip_prefix("ms,d"))
+            .or_else(|| s.strip_prefix(['M', 'm']))
+            .ok_or(RuleParseError::InvalidFormat)?
+            .split([',', ';'])
+            .map(|block| block.trim().parse::<u8>().or(Err(RuleParseError::InvalidFormat)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let blocks = <[u8; 16]>::try_from(blocks)
+            .or(Err(RuleParseError::InvalidFormat))?;
+
+        MargolusRule::new(blocks).ok_or(RuleParseError::NotReversible)
+    }
+}
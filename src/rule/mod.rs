@@ -0,0 +1,545 @@
+use std::rc::Rc;
+use std::str::FromStr;
+
+use wasm_bindgen::prelude::*;
+
+use crate::*;
+
+mod alternating;
+mod custom;
+mod cyclic;
+mod larger_than_life;
+mod life_like;
+mod margolus;
+mod notation;
+mod one_dimensional;
+mod species;
+mod stochastic;
+mod wireworld;
+
+pub use alternating::*;
+pub use custom::*;
+pub use cyclic::*;
+pub use larger_than_life::*;
+pub use life_like::*;
+pub use margolus::*;
+pub use notation::*;
+pub use one_dimensional::*;
+pub use species::*;
+pub use stochastic::*;
+pub use wireworld::*;
+
+/******************************************************************************
+ * Neighbourhood
+ *****************************************************************************/
+
+/// The shape of the neighbourhood of a rule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Neighbourhood {
+    /// The square of the cells at a Chebyshev distance of at most R.
+    Moore,
+    /// The diamond of the cells at a Manhattan distance of at most R.
+    VonNeumann,
+    /// The hexagon of the cells at a distance of at most R on an hexagonal
+    /// grid, emulated on the square grid by ignoring the north-east and
+    /// south-west directions (as Golly does).
+    Hexagonal,
+}
+
+impl Neighbourhood {
+    /// Get the columns offsets range of the row at `delta_row` of a
+    /// neighbourhood of range `range`.
+    fn row_span(&self, range: i32, delta_row: i32) -> (i32, i32) {
+        match self {
+            Neighbourhood::Moore => (-range, range),
+            Neighbourhood::VonNeumann => {
+                let half_width = range - delta_row.abs();
+                (-half_width, half_width)
+            },
+            Neighbourhood::Hexagonal => {
+                ((delta_row - range).max(-range), (delta_row + range).min(range))
+            },
+        }
+    }
+
+    /// Get the number of cells of a neighbourhood of range `range`, center
+    /// cell included.
+    fn size(&self, range: i32) -> u32 {
+        (-range..=range)
+            .map(|delta_row| {
+                let (min, max) = self.row_span(range, delta_row);
+                (max - min + 1) as u32
+            })
+            .sum()
+    }
+
+    /// Get the Golly suffix of the neighbourhood.
+    fn suffix(&self) -> char {
+        match self {
+            Neighbourhood::Moore => 'M',
+            Neighbourhood::VonNeumann => 'V',
+            Neighbourhood::Hexagonal => 'H',
+        }
+    }
+}
+
+/******************************************************************************
+ * Rule
+ *****************************************************************************/
+
+/// A cellular automaton rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Rule {
+    LifeLike(LifeLikeRule),
+    LargerThanLife(LargerThanLifeRule),
+    WireWorld(WireWorldRule),
+    OneDimensional(OneDimensionalRule),
+    Margolus(MargolusRule),
+    Species(SpeciesRule),
+    Custom(Rc<CustomRule>),
+    Stochastic(StochasticRule),
+    Cyclic(CyclicRule),
+    Alternating(AlternatingRule),
+}
+
+impl Default for Rule {
+    /// Conway's Game of Life, B3/S23.
+    fn default() -> Self {
+        Rule::LifeLike(LifeLikeRule::default())
+    }
+}
+
+impl From<LifeLikeRule> for Rule {
+    fn from(rule: LifeLikeRule) -> Self {
+        Rule::LifeLike(rule)
+    }
+}
+
+impl From<LargerThanLifeRule> for Rule {
+    fn from(rule: LargerThanLifeRule) -> Self {
+        Rule::LargerThanLife(rule)
+    }
+}
+
+impl From<WireWorldRule> for Rule {
+    fn from(rule: WireWorldRule) -> Self {
+        Rule::WireWorld(rule)
+    }
+}
+
+impl From<OneDimensionalRule> for Rule {
+    fn from(rule: OneDimensionalRule) -> Self {
+        Rule::OneDimensional(rule)
+    }
+}
+
+impl From<MargolusRule> for Rule {
+    fn from(rule: MargolusRule) -> Self {
+        Rule::Margolus(rule)
+    }
+}
+
+impl From<SpeciesRule> for Rule {
+    fn from(rule: SpeciesRule) -> Self {
+        Rule::Species(rule)
+    }
+}
+
+impl From<Rc<CustomRule>> for Rule {
+    fn from(rule: Rc<CustomRule>) -> Self {
+        Rule::Custom(rule)
+    }
+}
+
+impl From<StochasticRule> for Rule {
+    fn from(rule: StochasticRule) -> Self {
+        Rule::Stochastic(rule)
+    }
+}
+
+impl From<CyclicRule> for Rule {
+    fn from(rule: CyclicRule) -> Self {
+        Rule::Cyclic(rule)
+    }
+}
+
+impl From<AlternatingRule> for Rule {
+    fn from(rule: AlternatingRule) -> Self {
+        Rule::Alternating(rule)
+    }
+}
+
+impl Rule {
+    /// Get the number of states of the rule.
+    pub fn states(&self) -> u16 {
+        match self {
+            Rule::LifeLike(rule) => rule.states(),
+            Rule::LargerThanLife(rule) => rule.states(),
+            Rule::WireWorld(rule) => rule.states(),
+            Rule::OneDimensional(rule) => rule.states(),
+            Rule::Margolus(rule) => rule.states(),
+            Rule::Species(rule) => rule.states(),
+            Rule::Custom(rule) => rule.states(),
+            Rule::Stochastic(rule) => rule.rule().states(),
+            Rule::Cyclic(rule) => rule.states(),
+            Rule::Alternating(rule) => rule.states(),
+        }
+    }
+
+    /// Get the default color of a state, if the rule defines one.
+    pub fn color(&self, state: u8) -> Option<String> {
+        match self {
+            Rule::WireWorld(rule) => rule.color(state),
+            Rule::Species(rule) => rule.color(state),
+            Rule::Custom(rule) => rule.color(state),
+            Rule::Stochastic(rule) => rule.rule().color(state),
+            Rule::Cyclic(rule) => rule.color(state),
+            Rule::Alternating(rule) => rule.color(state),
+            _ => None,
+        }
+    }
+
+    /// Get the range of the rule, i.e. the largest distance, along rows or
+    /// columns, from which a cell can affect the next state of another cell.
+    pub fn range(&self) -> u32 {
+        match self {
+            Rule::LargerThanLife(rule) => rule.range(),
+            Rule::OneDimensional(rule) => rule.range() as u32,
+            Rule::Stochastic(rule) => rule.rule().range(),
+            Rule::Cyclic(rule) => rule.range(),
+            Rule::Alternating(rule) => rule.rules().iter().map(Rule::range).max().unwrap_or(1),
+            _ => 1,
+        }
+    }
+
+    /// Get the neighbourhood of the rule.
+    pub fn neighbourhood(&self) -> Neighbourhood {
+        match self {
+            Rule::LifeLike(rule) => rule.neighbourhood(),
+            Rule::LargerThanLife(rule) => rule.neighbourhood(),
+            Rule::Species(rule) => rule.neighbourhood(),
+            Rule::Custom(rule) => rule.neighbourhood(),
+            Rule::Stochastic(rule) => rule.rule().neighbourhood(),
+            Rule::Cyclic(rule) => rule.neighbourhood(),
+            Rule::Alternating(rule) => rule.rules()[0].neighbourhood(),
+            Rule::WireWorld(_)
+                | Rule::OneDimensional(_)
+                | Rule::Margolus(_) => Neighbourhood::Moore,
+        }
+    }
+
+    /// Get the rule in MAP notation, if the rule can be written in that
+    /// notation.
+    pub fn to_map(&self) -> Option<String> {
+        match self {
+            Rule::LifeLike(rule) => Some(rule.to_map()),
+            _ => None,
+        }
+    }
+
+    /// Get the next state of the background of a universe (see
+    /// `Universe::background`).
+    pub fn next_background(&self, universe: &Universe) -> Cell {
+        match self {
+            Rule::LifeLike(rule) => rule.next_background(universe.background),
+            Rule::Stochastic(rule) => rule.rule().next_background(universe),
+            Rule::Alternating(rule) => rule.current(universe).next_background(universe),
+            _ => Cell::Dead,
+        }
+    }
+
+    /// Check if the rule can update the cells of a universe one at a time
+    /// (see `next_cell`).
+    pub fn has_next_cell(&self) -> bool {
+        match self {
+            Rule::OneDimensional(_) => false,
+            Rule::Stochastic(rule) => rule.rule().has_next_cell(),
+            Rule::Alternating(rule) => rule.rules().iter().all(Rule::has_next_cell),
+            _ => true,
+        }
+    }
+
+    /// Check if a universe of `width` by `height` cells can have the rule,
+    /// Margolus rules needing an even width and height (see `MargolusRule`).
+    pub fn fits(&self, width: i32, height: i32) -> bool {
+        match self {
+            Rule::Margolus(_) => width%2 == 0 && height%2 == 0,
+            Rule::Stochastic(rule) => rule.rule().fits(width, height),
+            Rule::Alternating(rule) => rule.rules().iter().all(|rule| rule.fits(width, height)),
+            _ => true,
+        }
+    }
+
+    /// Check if the rule depends on the phase given by the generation number,
+    /// i.e. if it is an alternating or a Margolus rule.
+    pub fn has_phase(&self) -> bool {
+        match self {
+            Rule::Margolus(_) | Rule::Alternating(_) => true,
+            Rule::Stochastic(rule) => rule.rule().has_phase(),
+            _ => false,
+        }
+    }
+
+    /// Get the next state of a live cell of a universe which fails to survive,
+    /// i.e. its next decaying state with Generations rules (see `decay`), or
+    /// the dead state.
+    pub fn death(&self, universe: &Universe, cell: Cell) -> Cell {
+        match self {
+            Rule::LifeLike(rule) => decay(cell, rule.states()),
+            Rule::LargerThanLife(rule) => decay(cell, rule.states()),
+            Rule::Stochastic(rule) => rule.rule().death(universe, cell),
+            Rule::Alternating(rule) => rule.current(universe).death(universe, cell),
+            _ => Cell::Dead,
+        }
+    }
+
+    /// Compute the next state of the cell at (`col`, `row`) of a universe
+    /// alone, as used by asynchronous schedules (see `Universe::schedule`).
+    ///
+    /// Life-like rules give the actual state of the cell, whatever the
+    /// background of the universe. One-dimensional rules, which compute a
+    /// whole row at once, give `None`.
+    pub fn next_cell(&self, universe: &Universe, col: i32, row: i32) -> Option<Cell> {
+        match self {
+            Rule::LifeLike(rule) => Some(rule.next_cell(universe, col, row)),
+            Rule::LargerThanLife(rule) => Some(rule.next_cell(universe, col, row)),
+            Rule::WireWorld(rule) => Some(rule.next_cell(universe, col, row)),
+            Rule::OneDimensional(_) => None,
+            Rule::Margolus(rule) => Some(rule.next_cell(universe, col, row)),
+            Rule::Species(rule) => Some(rule.next_cell(universe, col, row)),
+            Rule::Custom(rule) => Some(rule.next_cell(universe, col, row)),
+            Rule::Stochastic(rule) => rule.next_cell(universe, col, row),
+            Rule::Cyclic(rule) => Some(rule.next_cell(universe, col, row)),
+            Rule::Alternating(rule) => rule.current(universe).next_cell(universe, col, row),
+        }
+    }
+
+    /// Compute the next generation of a universe.
+    pub fn next_generation(&self, universe: &Universe) -> Vec<Cell> {
+        match self {
+            Rule::LifeLike(rule) => rule.next_generation(universe),
+            Rule::LargerThanLife(rule) => rule.next_generation(universe),
+            Rule::WireWorld(rule) => rule.next_generation(universe),
+            Rule::OneDimensional(rule) => rule.next_generation(universe),
+            Rule::Margolus(rule) => rule.next_generation(universe),
+            Rule::Species(rule) => rule.next_generation(universe),
+            Rule::Custom(rule) => rule.next_generation(universe),
+            Rule::Stochastic(rule) => rule.next_generation(universe),
+            Rule::Cyclic(rule) => rule.next_generation(universe),
+            Rule::Alternating(rule) => rule.current(universe).next_generation(universe),
+        }
+    }
+}
+
+/// Get the next state of a cell which neither is born nor survives.
+///
+/// Live cells of multi-state rules go through the states 2 to N-1 before
+/// dying.
+fn decay(cell: Cell, states: u16) -> Cell {
+    if cell == Cell::Dead {
+        return Cell::Dead;
+    }
+
+    let state = cell.state() as u16 + 1;
+
+    if state < states {
+        Cell::from(state as u8)
+    } else {
+        Cell::Dead
+    }
+}
+
+/******************************************************************************
+ * Encoder
+ *****************************************************************************/
+
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Rule::LifeLike(rule) => rule.fmt(f),
+            Rule::LargerThanLife(rule) => rule.fmt(f),
+            Rule::WireWorld(rule) => rule.fmt(f),
+            Rule::OneDimensional(rule) => rule.fmt(f),
+            Rule::Margolus(rule) => rule.fmt(f),
+            Rule::Species(rule) => rule.fmt(f),
+            Rule::Custom(rule) => rule.fmt(f),
+            Rule::Stochastic(rule) => rule.fmt(f),
+            Rule::Cyclic(rule) => rule.fmt(f),
+            Rule::Alternating(rule) => rule.fmt(f),
+        }
+    }
+}
+
+/******************************************************************************
+ * Decoder
+ *****************************************************************************/
+
+pub enum RuleParseError {
+    InvalidFormat,
+    InvalidCount,
+    InvalidLetter,
+    InvalidStates,
+    InvalidRange,
+    InvalidNeighbourhood,
+    NotReversible,
+    InvalidVariable,
+    InvalidSymmetries,
+    InvalidTransition,
+    InvalidTree,
+    InvalidColor,
+    InvalidProbability,
+    InvalidNotation,
+    ReservedName,
+    InvalidSize,
+}
+
+impl std::fmt::Display for RuleParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RuleParseError::InvalidFormat => write!(f, "invalid rule format"),
+            RuleParseError::InvalidCount => write!(f, "invalid neighbour count"),
+            RuleParseError::InvalidLetter => write!(f, "invalid neighbourhood letter"),
+            RuleParseError::InvalidStates => write!(f, "invalid number of states"),
+            RuleParseError::InvalidRange => write!(f, "invalid range"),
+            RuleParseError::InvalidNeighbourhood => write!(f, "invalid neighbourhood"),
+            RuleParseError::NotReversible => write!(f, "rule is not reversible"),
+            RuleParseError::InvalidVariable => write!(f, "invalid variable"),
+            RuleParseError::InvalidSymmetries => write!(f, "invalid symmetries"),
+            RuleParseError::InvalidTransition => write!(f, "invalid transition"),
+            RuleParseError::InvalidTree => write!(f, "invalid rule tree"),
+            RuleParseError::InvalidColor => write!(f, "invalid color"),
+            RuleParseError::InvalidProbability => write!(f, "invalid probability"),
+            RuleParseError::InvalidNotation => write!(f, "invalid notation"),
+            RuleParseError::ReservedName => write!(f, "rule name is a built-in rule"),
+            RuleParseError::InvalidSize => write!(f, "rule needs an even width and height"),
+        }
+    }
+}
+
+impl From<RuleParseError> for JsValue {
+    fn from(err: RuleParseError) -> Self {
+        JsValue::from_str(&err.to_string())
+    }
+}
+
+/// Parse a single rule, i.e. any rule but a sequence of alternating rules.
+///
+/// Built-in rules come first, so that the name of a rule loaded from a rule
+/// file never hides one of them.
+fn parse_rule(s: &str) -> Result<Rule, RuleParseError> {
+    let s = s.trim();
+
+    parse_builtin_rule(s).or_else(|err| match CustomRule::registered(s) {
+        Some(rule) => Ok(Rule::from(rule)),
+        None => Err(err),
+    })
+}
+
+/// Parse a single built-in rule.
+fn parse_builtin_rule(s: &str) -> Result<Rule, RuleParseError> {
+    if s.contains('@') {
+        s.parse::<StochasticRule>().map(Rule::from)
+    } else if let Ok(rule) = s.parse::<WireWorldRule>() {
+        Ok(Rule::from(rule))
+    } else if let Ok(rule) = s.parse::<SpeciesRule>() {
+        Ok(Rule::from(rule))
+    } else if s.starts_with(['W', 'w', 'T', 't']) {
+        s.parse::<OneDimensionalRule>().map(Rule::from)
+    } else if s.starts_with("MAP") || s.starts_with("map") {
+        s.parse::<LifeLikeRule>().map(Rule::from)
+    } else if s.starts_with(['M', 'm']) {
+        s.parse::<MargolusRule>().map(Rule::from)
+    } else if s.starts_with(['R', 'r']) && s.contains(',') {
+        s.parse::<LargerThanLifeRule>().map(Rule::from)
+    } else if s.starts_with(['R', 'r']) && s.contains('/') {
+        s.parse::<CyclicRule>().map(Rule::from)
+    } else {
+        s.parse::<LifeLikeRule>().map(Rule::from)
+    }
+}
+
+impl FromStr for Rule {
+    type Err = RuleParseError;
+
+    /// Parse a rule, either a named rule (e.g. "WireWorld"), a species rule
+    /// (e.g. "QuadLife"), a Larger than Life rule (e.g.
+    /// "R5,C0,M1,S34..58,B34..45,NM"), a cyclic or Greenberg-Hastings rule
+    /// (e.g. "R1/T3/C3/NM" or "R1/T1/C8/NM/GH"), a one-dimensional rule (e.g.
+    /// "W30" or "T777,K3"), a Margolus rule (e.g.
+    /// "M0,8,4,3,2,5,9,7,1,6,10,11,12,13,14,15"), a Life-like rule (e.g.
+    /// "B3/S23") or the name of a rule loaded from a rule file (see
+    /// `CustomRule`), possibly followed by the probabilities of births and
+    /// survivals (e.g. "B3/S23@1,0.99", see `StochasticRule`), or a sequence
+    /// of such rules separated by hyphens (e.g. "B3/S23-B36/S125", see
+    /// `AlternatingRule`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_rule(s).or_else(|err| match s.parse::<AlternatingRule>() {
+            Ok(rule) => Ok(Rule::from(rule)),
+            Err(_) => Err(err),
+        })
+    }
+}
+
+/******************************************************************************
+ * Universe
+ *****************************************************************************/
+
+#[wasm_bindgen]
+impl Universe {
+/// Get the rule of the universe.
+pub fn rule(&self) -> String {
+    self.rule.to_string()
+}
+
+/// Get the rule of the universe in MAP notation, or `undefined` if the rule
+/// cannot be written in that notation.
+pub fn rule_map(&self) -> Option<String> {
+    self.rule.to_map()
+}
+
+/// Get the number of cell states of the rule of the universe.
+pub fn states(&self) -> u16 {
+    self.rule.states()
+}
+
+/// Set the rule of the universe.
+///
+/// Accepts B/S (e.g. "B36/S23" or "b36s23") or S/B (e.g. "23/36") notations,
+/// with optional Hensel notation letters (e.g. "B2-a/S12"), an optional number of
+/// states for Generations rules (e.g. "B2/S/C3"), and an optional "V" or "H"
+/// suffix for the von Neumann or hexagonal neighbourhoods (e.g. "B2/S34H"),
+/// Larger than Life rules (e.g. "R5,C0,M1,S34..58,B34..45,NM"), cyclic rules
+/// (e.g. "R1/T3/C3/NM") and Greenberg-Hastings rules (e.g. "R1/T1/C8/NM/GH"),
+/// one-dimensional Wolfram rules (e.g. "W30") and totalistic rules (e.g.
+/// "T777,K3,R1"), rules in MAP notation (i.e. "MAP" followed by the base64
+/// transition table), Wolfram codes of Life-like rules (e.g. "C224" for
+/// "B3/S23", see `convert_rule`), reversible Margolus rules (e.g.
+/// "M0,8,4,3,2,5,9,7,1,6,10,11,12,13,14,15"), "WireWorld", the coloured
+/// species rules "Immigration" and "QuadLife", optionally prefixed with a
+/// Life-like rule (e.g. "B36/S23Immigration"), and the names
+/// of the rules loaded with `load_rule`.
+///
+/// Any rule may be followed by "@" and the probabilities of births and
+/// survivals (e.g. "B3/S23@1,0.99" for 1% of spontaneous deaths), see
+/// `set_seed` to reproduce runs of such stochastic rules.
+///
+/// Rules separated by hyphens (e.g. "B3/S23-B36/S125") are applied in turn,
+/// the rule computing a generation being chosen by the generation number
+/// modulo the number of rules.
+///
+/// Margolus rules need a universe with an even width and height.
+///
+/// If the background of the universe is alive, the cells are first set to
+/// their actual state, the background becoming dead. One-dimensional rules
+/// continue from the last row which is not empty.
+pub fn set_rule(&mut self, rule: &str) -> Result<(), RuleParseError> {
+    let rule = rule.parse::<Rule>()?;
+    if !rule.fits(self.width, self.height) {
+        return Err(RuleParseError::InvalidSize);
+    }
+
+    self.rule = rule;
+    self.clear_background();
+    self.find_current_row();
+    Ok(())
+}}
@@ -0,0 +1,118 @@
+use std::str::FromStr;
+
+use super::*;
+
+/******************************************************************************
+ * Notations
+ *****************************************************************************/
+
+/// A notation of Life-like rules.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuleNotation {
+    /// The B/S notation, e.g. "B3/S23", with Hensel notation letters for
+    /// non-totalistic isotropic rules, e.g. "B2-a/S12".
+    BS,
+    /// The older S/B notation of totalistic rules, e.g. "23/3".
+    SB,
+    /// Wolfram's code of 2 states totalistic rules with the Moore
+    /// neighbourhood, prefixed with "C", e.g. "C224".
+    Wolfram,
+    /// The MAP notation of rules with the Moore neighbourhood, i.e. "MAP"
+    /// followed by the base64 transition table.
+    Map,
+}
+
+impl RuleNotation {
+    /// All the notations, in order of preference.
+    pub const ALL: [RuleNotation; 4] = [
+        RuleNotation::BS,
+        RuleNotation::SB,
+        RuleNotation::Wolfram,
+        RuleNotation::Map,
+    ];
+}
+
+impl std::fmt::Display for RuleNotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RuleNotation::BS => write!(f, "B/S"),
+            RuleNotation::SB => write!(f, "S/B"),
+            RuleNotation::Wolfram => write!(f, "Wolfram"),
+            RuleNotation::Map => write!(f, "MAP"),
+        }
+    }
+}
+
+impl FromStr for RuleNotation {
+    type Err = RuleParseError;
+
+    /// Parse the name of a notation, i.e. "B/S", "S/B", "Wolfram" or "MAP",
+    /// case insensitively and with or without the "/".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "b/s" | "bs" => Ok(RuleNotation::BS),
+            "s/b" | "sb" => Ok(RuleNotation::SB),
+            "wolfram" => Ok(RuleNotation::Wolfram),
+            "map" => Ok(RuleNotation::Map),
+            _ => Err(RuleParseError::InvalidNotation),
+        }
+    }
+}
+
+/******************************************************************************
+ * Rules
+ *****************************************************************************/
+
+impl Rule {
+    /// Write the rule in the given notation, if the rule can be written in
+    /// that notation.
+    pub fn to_notation(&self, notation: RuleNotation) -> Option<String> {
+        let rule = match self {
+            Rule::LifeLike(rule) => rule,
+            _ => return None,
+        };
+
+        match notation {
+            RuleNotation::BS => rule.to_bs(),
+            RuleNotation::SB => rule.to_sb(),
+            RuleNotation::Wolfram => rule.to_wolfram().map(|code| format!("C{}", code)),
+            RuleNotation::Map => self.to_map(),
+        }
+    }
+
+    /// Get the notations the rule can be written in.
+    pub fn notations(&self) -> Vec<RuleNotation> {
+        RuleNotation::ALL
+            .iter()
+            .copied()
+            .filter(|&notation| self.to_notation(notation).is_some())
+            .collect()
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+/// Get the canonical form of a rule, i.e. the form given by `rule` once set,
+/// so that equivalent rules (e.g. "23/3", "B3/S23", "b3s23" and "C224") have
+/// the same canonical form.
+pub fn canonical_rule(rule: &str) -> Result<String, RuleParseError> {
+    Ok(rule.parse::<Rule>()?.to_string())
+}
+
+/// Convert a rule to a notation among "B/S", "S/B", "Wolfram" and "MAP", or
+/// get `undefined` if the rule cannot be written in that notation.
+pub fn convert_rule(rule: &str, notation: &str) -> Result<Option<String>, RuleParseError> {
+    let notation = notation.parse::<RuleNotation>()?;
+    Ok(rule.parse::<Rule>()?.to_notation(notation))
+}
+
+/// Get the notations among "B/S", "S/B", "Wolfram" and "MAP" a rule can be
+/// written in.
+pub fn rule_notations(rule: &str) -> Result<Vec<String>, RuleParseError> {
+    Ok(rule
+        .parse::<Rule>()?
+        .notations()
+        .iter()
+        .map(RuleNotation::to_string)
+        .collect())
+}}
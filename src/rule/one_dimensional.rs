@@ -0,0 +1,241 @@
+use std::str::FromStr;
+
+use wasm_bindgen::prelude::*;
+
+use super::*;
+
+/******************************************************************************
+ * Rule
+ *****************************************************************************/
+
+/// A one-dimensional cellular automaton rule.
+///
+/// One-dimensional rules run on a single row of the universe, each generation
+/// being written on the row below the previous one so that the universe shows
+/// the space-time diagram of the automaton. Once the last row is reached, the
+/// universe scrolls up by one row at each generation.
+///
+/// The row of the current generation follows the generation number of the
+/// universe. When the rule is set, it is the last row which is not empty, so
+/// that the rule continues the pattern of the universe.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OneDimensionalRule {
+    /// A Wolfram elementary rule, from 0 to 255.
+    Elementary(u8),
+    /// A totalistic rule with `colours` states and cells seeing the `range`
+    /// cells on each side, the next state of a cell being the digit of `code`
+    /// in base `colours` at the position given by the sum of the states.
+    Totalistic {
+        code: u64,
+        colours: u8,
+        range: u8,
+    },
+}
+
+impl OneDimensionalRule {
+    /// The maximum range of a totalistic rule.
+    pub const MAX_RANGE: u8 = 8;
+
+    /// Get the number of states of the rule.
+    pub fn states(&self) -> u16 {
+        match self {
+            OneDimensionalRule::Elementary(_) => 2,
+            OneDimensionalRule::Totalistic { colours, .. } => *colours as u16,
+        }
+    }
+
+    pub(super) fn range(&self) -> i32 {
+        match self {
+            OneDimensionalRule::Elementary(_) => 1,
+            OneDimensionalRule::Totalistic { range, .. } => *range as i32,
+        }
+    }
+
+    /// Compute the next state of a cell given the states of the cells of its
+    /// neighbourhood, from left to right.
+    pub fn next_state(&self, neighbourhood: &[u8]) -> Cell {
+        match self {
+            OneDimensionalRule::Elementary(rule) => {
+                let index = neighbourhood
+                    .iter()
+                    .fold(0, |index, &state| 2*index + (state != 0) as u8);
+                Cell::from((rule >> index) & 1)
+            },
+            OneDimensionalRule::Totalistic { code, colours, .. } => {
+                let colours = *colours as u64;
+                let sum = neighbourhood
+                    .iter()
+                    .map(|&state| (state as u64).min(colours - 1))
+                    .sum::<u64>();
+
+                let digit = colours
+                    .checked_pow(sum as u32)
+                    .map_or(0, |weight| (code/weight)%colours);
+                Cell::from(digit as u8)
+            },
+        }
+    }
+
+    /// Compute the next generation of a universe.
+    pub fn next_generation(&self, universe: &Universe) -> Vec<Cell> {
+        let width = universe.width as usize;
+        let row = universe.generation.min(universe.height as u32 - 1) as i32;
+        let range = self.range();
+
+        let next_row = (0..universe.width)
+            .map(|col| {
+                let neighbourhood = (col - range..=col + range)
+                    .map(|col| {
+                        if universe.fixed_boundary
+                            && (col < 0 || col >= universe.width) {
+                            0
+                        } else {
+                            universe.neighbour(col, row).state()
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                self.next_state(&neighbourhood)
+            })
+            .collect::<Vec<_>>();
+
+        let mut cells = universe.cells.clone();
+        if row + 1 < universe.height {
+            let start = (row + 1) as usize*width;
+            cells[start..start + width].copy_from_slice(&next_row);
+        } else {
+            cells.drain(..width);
+            cells.extend(next_row);
+        }
+
+        cells
+    }
+}
+
+/******************************************************************************
+ * Encoder
+ *****************************************************************************/
+
+impl std::fmt::Display for OneDimensionalRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            OneDimensionalRule::Elementary(rule) => write!(f, "W{}", rule),
+            OneDimensionalRule::Totalistic { code, colours, range } => {
+                write!(f, "T{},K{},R{}", code, colours, range)
+            },
+        }
+    }
+}
+
+/******************************************************************************
+ * Decoder
+ *****************************************************************************/
+
+fn parse_value<T: FromStr>(s: &str, prefix: char) -> Result<T, RuleParseError> {
+    s.trim()
+        .strip_prefix([prefix, prefix.to_ascii_lowercase()])
+        .ok_or(RuleParseError::InvalidFormat)?
+        .parse::<T>()
+        .or(Err(RuleParseError::InvalidFormat))
+}
+
+impl FromStr for OneDimensionalRule {
+    type Err = RuleParseError;
+
+    /// Parse a Wolfram elementary rule (e.g. "W30"), or a totalistic rule
+    /// given by its code, number of colours and range (e.g. "T777,K3,R1", the
+    /// range being 1 if omitted).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s.trim().split(',').collect::<Vec<_>>();
+
+        match parts[..] {
+            [rule] => {
+                Ok(OneDimensionalRule::Elementary(parse_value(rule, 'W')?))
+            },
+            [code, colours] | [code, colours, _] => {
+                let code = parse_value::<u64>(code, 'T')?;
+                let colours = parse_value::<u8>(colours, 'K')?;
+                let range = match parts.get(2) {
+                    Some(range) => parse_value::<u8>(range, 'R')?,
+                    None => 1,
+                };
+
+                if colours < 2 {
+                    return Err(RuleParseError::InvalidStates);
+                }
+
+                if !(1..=Self::MAX_RANGE).contains(&range) {
+                    return Err(RuleParseError::InvalidRange);
+                }
+
+                // The code has one digit per possible sum of the states.
+                let digits = (2*range as u32 + 1)*(colours as u32 - 1) + 1;
+                let max = (colours as u64).checked_pow(digits);
+                if max.is_some_and(|max| code >= max) {
+                    return Err(RuleParseError::InvalidFormat);
+                }
+
+                Ok(OneDimensionalRule::Totalistic { code, colours, range })
+            },
+            _ => Err(RuleParseError::InvalidFormat),
+        }
+    }
+}
+
+/******************************************************************************
+ * Universe
+ *****************************************************************************/
+
+impl Universe {
+    /// Set the generation number of a universe with a one-dimensional rule so
+    /// that the row of its current generation is its last row which is not
+    /// empty, or its first row.
+    ///
+    /// The generation number is kept if it already gives that row.
+    pub(crate) fn find_current_row(&mut self) {
+        if let Rule::OneDimensional(_) = self.rule {
+            let row = self.cells
+                .chunks(self.width as usize)
+                .rposition(|row| row.iter().any(|&cell| cell != Cell::Dead))
+                .unwrap_or(0) as u32;
+
+            if self.generation.min(self.height as u32 - 1) != row {
+                self.generation = row;
+            }
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+/// Check if the cells beyond the left and right edges of the universe are
+/// considered dead by one-dimensional rules.
+///
+/// They always are on a bounded universe (see `set_topology`).
+pub fn fixed_boundary(&self) -> bool {
+    self.fixed_boundary
+}
+
+/// Set whether the cells beyond the left and right edges of the universe are
+/// considered dead by one-dimensional rules, or if the row wraps around.
+pub fn set_fixed_boundary(&mut self, fixed_boundary: bool) {
+    self.fixed_boundary = fixed_boundary;
+}
+
+/// Reset the universe to the initial row of a one-dimensional rule.
+///
+/// All cells are cleared, then the first row is set either to a single live
+/// cell in its middle, or to random states drawn with the random number
+/// generator of the universe.
+pub fn reset_row(&mut self, random: bool) {
+    let states = self.rule.states() as u32;
+
+    self.clear();
+    if random {
+        for col in 0..self.width {
+            let state = self.random.next_below(states) as u8;
+            self.cells[col as usize] = Cell::from(state);
+        }
+    } else {
+        self.cells[(self.width/2) as usize] = Cell::Alive;
+    }
+}}
@@ -0,0 +1,171 @@
+use std::str::FromStr;
+
+use super::*;
+
+/******************************************************************************
+ * Rule
+ *****************************************************************************/
+
+/// A Life-like rule in which live cells belong to one of several species,
+/// like Immigration (2 species) or QuadLife (4 species).
+///
+/// Each species is a live state, from 1 to the number of species. Surviving
+/// cells keep their species, and born cells take the majority species of their
+/// live neighbours. On a tie, born cells take the first species missing from
+/// their neighbours if any, so that a cell born from 3 neighbours of distinct
+/// species in QuadLife takes the fourth species, or the first tied species
+/// otherwise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpeciesRule {
+    rule: LifeLikeRule,
+    species: u8,
+}
+
+impl SpeciesRule {
+    /// Create a rule with `species` species from a 2 states Life-like rule.
+    pub fn new(rule: LifeLikeRule, species: u8) -> Self {
+        SpeciesRule { rule, species }
+    }
+
+    /// Get the number of states of the rule.
+    pub fn states(&self) -> u16 {
+        self.species as u16 + 1
+    }
+
+    /// Get the number of species of the rule.
+    pub fn species(&self) -> u8 {
+        self.species
+    }
+
+    /// Get the neighbourhood of the rule.
+    pub fn neighbourhood(&self) -> Neighbourhood {
+        self.rule.neighbourhood()
+    }
+
+    /// Get the default color of a species, the first species being left to
+    /// the alive cells color of the theme.
+    pub fn color(&self, state: u8) -> Option<String> {
+        let colors: &[&str] = match self.species {
+            2 => &["#ffff00"],
+            _ => &["#00ff00", "#0080ff", "#ffff00"],
+        };
+
+        colors
+            .get((state as usize).checked_sub(2)?)
+            .map(|&color| String::from(color))
+    }
+
+    /// Get the species of a born cell given the number of live neighbours of
+    /// each species, the first count being the one of the dead cells.
+    fn birth_species(&self, counts: &[u32]) -> Cell {
+        let species = &counts[1..=self.species as usize];
+        let max = *species.iter().max().unwrap();
+        let mut majority = (1..).zip(species).filter(|&(_, &count)| count == max);
+        let (first, _) = majority.next().unwrap();
+
+        if majority.next().is_none() {
+            return Cell::from(first);
+        }
+
+        (1..).zip(species)
+            .find(|&(_, &count)| count == 0)
+            .map_or(Cell::from(first), |(missing, _)| Cell::from(missing))
+    }
+
+    /// Compute the next state of the cell at (`col`, `row`) of a universe.
+    pub fn next_cell(&self, universe: &Universe, col: i32, row: i32) -> Cell {
+        let neighbourhood = self.rule.neighbourhood();
+        let mut counts = vec![0; self.states() as usize];
+        let mut index = 0;
+
+        for delta_row in -1..=1 {
+            let (min, max) = neighbourhood.row_span(1, delta_row);
+            for delta_col in min..=max {
+                let state = universe.neighbour(col + delta_col, row + delta_row).state();
+                if state != 0 && state as u16 <= self.species as u16 {
+                    index |= neighbourhood_bit(delta_col, delta_row);
+                    counts[state as usize] += 1;
+                }
+            }
+        }
+
+        match (universe.cells[universe.get_index(col, row)], self.rule.entry(index)) {
+            (_, false) => Cell::Dead,
+            (Cell::Dead, true) => self.birth_species(&counts),
+            (cell, true) => cell,
+        }
+    }
+
+    /// Compute the next generation of a universe.
+    pub fn next_generation(&self, universe: &Universe) -> Vec<Cell> {
+        let mut cells = universe.cells.clone();
+
+        for row in 0..universe.height {
+            for col in 0..universe.width {
+                cells[universe.get_index(col, row)] = self.next_cell(universe, col, row);
+            }
+        }
+
+        cells
+    }
+}
+
+/******************************************************************************
+ * Encoder
+ *****************************************************************************/
+
+impl SpeciesRule {
+    fn suffix(&self) -> &'static str {
+        match self.species {
+            2 => "Immigration",
+            _ => "QuadLife",
+        }
+    }
+}
+
+impl std::fmt::Display for SpeciesRule {
+    /// Write the rule as "Immigration" or "QuadLife", prefixed with its
+    /// Life-like rule unless it is Conway's Game of Life (e.g.
+    /// "B36/S23QuadLife").
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.rule != LifeLikeRule::default() {
+            write!(f, "{}", self.rule)?;
+        }
+        write!(f, "{}", self.suffix())
+    }
+}
+
+/******************************************************************************
+ * Decoder
+ *****************************************************************************/
+
+impl FromStr for SpeciesRule {
+    type Err = RuleParseError;
+
+    /// Parse "Immigration" or "QuadLife", case insensitively, optionally
+    /// prefixed with a 2 states Life-like rule (e.g. "B36/S23QuadLife"),
+    /// Conway's Game of Life being the default.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let lowercase = s.to_ascii_lowercase();
+
+        let (prefix, species) = if lowercase.ends_with("immigration") {
+            (&s[..s.len() - "immigration".len()], 2)
+        } else if lowercase.ends_with("quadlife") {
+            (&s[..s.len() - "quadlife".len()], 4)
+        } else {
+            return Err(RuleParseError::InvalidFormat);
+        };
+
+        let rule = match prefix {
+            "" => LifeLikeRule::default(),
+            prefix => prefix.parse::<LifeLikeRule>()?,
+        };
+
+        if rule.states() != 2 {
+            return Err(RuleParseError::InvalidStates);
+        }
+
+        Ok(SpeciesRule::new(rule, species))
+    }
+}
@@ -0,0 +1,144 @@
+use std::str::FromStr;
+
+use super::*;
+
+/// Probabilities are stored in parts per million, to keep rules comparable.
+const PROBABILITY_SCALE: f64 = 1_000_000.;
+
+/******************************************************************************
+ * Rule
+ *****************************************************************************/
+
+/// A rule in which births and survivals only happen with some probability.
+///
+/// The next generation is first computed by the underlying rule. A cell being
+/// born then stays dead with a probability of 1 - `birth`, and a cell keeping
+/// its state dies (see `Rule::death`) with a probability of 1 - `survival`,
+/// using the random number generator of the universe.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StochasticRule {
+    rule: Box<Rule>,
+    birth: u32,
+    survival: u32,
+}
+
+impl StochasticRule {
+    /// Create a rule from an underlying rule and the probabilities of births
+    /// and survivals, which are clamped to the range 0 to 1.
+    pub fn new(rule: Rule, birth: f64, survival: f64) -> Self {
+        let scaled = |probability: f64| {
+            (probability.clamp(0., 1.)*PROBABILITY_SCALE).round() as u32
+        };
+
+        StochasticRule {
+            rule: Box::new(rule),
+            birth: scaled(birth),
+            survival: scaled(survival),
+        }
+    }
+
+    /// Get the underlying rule.
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    /// Get the probability of births.
+    pub fn birth(&self) -> f64 {
+        self.birth as f64/PROBABILITY_SCALE
+    }
+
+    /// Get the probability of survivals.
+    pub fn survival(&self) -> f64 {
+        self.survival as f64/PROBABILITY_SCALE
+    }
+
+    /// Draw whether an event of the given probability happens. No number is
+    /// drawn for certain events.
+    fn happens(&self, probability: u32, random: &Random) -> bool {
+        probability as f64 >= PROBABILITY_SCALE
+            || random.next_f64()*PROBABILITY_SCALE < probability as f64
+    }
+
+    /// Get the actual next state of a cell given the next state computed by
+    /// the underlying rule.
+    fn chance(&self, universe: &Universe, cell: Cell, next_cell: Cell) -> Cell {
+        let random = &universe.random;
+
+        if cell == Cell::Dead && next_cell != Cell::Dead {
+            if self.happens(self.birth, random) {
+                next_cell
+            } else {
+                cell
+            }
+        } else if cell != Cell::Dead && next_cell == cell {
+            if self.happens(self.survival, random) {
+                next_cell
+            } else {
+                self.rule.death(universe, cell)
+            }
+        } else {
+            next_cell
+        }
+    }
+
+    /// Compute the next state of the cell at (`col`, `row`) of a universe, if
+    /// the underlying rule can update cells one at a time.
+    pub fn next_cell(&self, universe: &Universe, col: i32, row: i32) -> Option<Cell> {
+        let cell = universe.cells[universe.get_index(col, row)];
+        let next_cell = self.rule.next_cell(universe, col, row)?;
+
+        Some(self.chance(universe, cell, next_cell))
+    }
+
+    /// Compute the next generation of a universe.
+    pub fn next_generation(&self, universe: &Universe) -> Vec<Cell> {
+        universe.cells
+            .iter()
+            .zip(self.rule.next_generation(universe))
+            .map(|(&cell, next_cell)| self.chance(universe, cell, next_cell))
+            .collect()
+    }
+}
+
+/******************************************************************************
+ * Encoder
+ *****************************************************************************/
+
+impl std::fmt::Display for StochasticRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}@{},{}", self.rule, self.birth(), self.survival())
+    }
+}
+
+/******************************************************************************
+ * Decoder
+ *****************************************************************************/
+
+fn parse_probability(s: &str) -> Result<f64, RuleParseError> {
+    s.trim()
+        .parse::<f64>().ok()
+        .filter(|probability| (0. ..=1.).contains(probability))
+        .ok_or(RuleParseError::InvalidProbability)
+}
+
+impl FromStr for StochasticRule {
+    type Err = RuleParseError;
+
+    /// Parse a rule followed by "@" and the probabilities of births and
+    /// survivals, e.g. "B3/S23@1,0.99" for Conway's Game of Life with 1% of
+    /// spontaneous deaths.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (rule, probabilities) = s
+            .split_once('@')
+            .ok_or(RuleParseError::InvalidFormat)?;
+        let (birth, survival) = probabilities
+            .split_once(',')
+            .ok_or(RuleParseError::InvalidProbability)?;
+
+        Ok(StochasticRule::new(
+            rule.parse()?,
+            parse_probability(birth)?,
+            parse_probability(survival)?,
+        ))
+    }
+}
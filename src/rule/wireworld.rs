@@ -0,0 +1,104 @@
+use std::str::FromStr;
+
+use super::*;
+
+/******************************************************************************
+ * Rule
+ *****************************************************************************/
+
+/// Brian Silverman's Wireworld.
+///
+/// Cells are either empty (state 0), electron heads (state 1), electron tails
+/// (state 2) or conductors (state 3). Heads become tails, tails become
+/// conductors, and conductors become heads when exactly one or two of their 8
+/// neighbours are heads.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WireWorldRule;
+
+impl WireWorldRule {
+    pub const EMPTY: Cell = Cell::Dead;
+    pub const HEAD: Cell = Cell::Alive;
+    pub const TAIL: Cell = Cell(2);
+    pub const CONDUCTOR: Cell = Cell(3);
+
+    /// Get the number of states of the rule.
+    pub fn states(&self) -> u16 {
+        4
+    }
+
+    /// Get the default color of a state, following Golly's colors.
+    pub fn color(&self, state: u8) -> Option<String> {
+        match Cell::from(state) {
+            Self::HEAD => Some(String::from("#0080ff")),
+            Self::TAIL => Some(String::from("#ffffff")),
+            Self::CONDUCTOR => Some(String::from("#ff8000")),
+            _ => None,
+        }
+    }
+
+    /// Compute the next state of a cell given its neighbourhood index, in
+    /// which heads are the live cells.
+    pub fn next_state(&self, cell: Cell, neighbourhood: u16) -> Cell {
+        match cell {
+            Self::HEAD => Self::TAIL,
+            Self::TAIL => Self::CONDUCTOR,
+            Self::CONDUCTOR => {
+                let heads = (neighbourhood & !NEIGHBOURHOOD_CENTER).count_ones();
+                if heads == 1 || heads == 2 {
+                    Self::HEAD
+                } else {
+                    Self::CONDUCTOR
+                }
+            },
+            _ => Self::EMPTY,
+        }
+    }
+
+    /// Compute the next state of the cell at (`col`, `row`) of a universe.
+    pub fn next_cell(&self, universe: &Universe, col: i32, row: i32) -> Cell {
+        match universe.cells[universe.get_index(col, row)] {
+            Self::EMPTY => Self::EMPTY,
+            cell => self.next_state(cell, universe.neighbourhood(col, row)),
+        }
+    }
+
+    /// Compute the next generation of a universe.
+    pub fn next_generation(&self, universe: &Universe) -> Vec<Cell> {
+        let mut cells = universe.cells.clone();
+
+        for row in 0..universe.height {
+            for col in 0..universe.width {
+                cells[universe.get_index(col, row)] = self.next_cell(universe, col, row);
+            }
+        }
+
+        cells
+    }
+}
+
+/******************************************************************************
+ * Encoder
+ *****************************************************************************/
+
+impl std::fmt::Display for WireWorldRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "WireWorld")
+    }
+}
+
+/******************************************************************************
+ * Decoder
+ *****************************************************************************/
+
+impl FromStr for WireWorldRule {
+    type Err = RuleParseError;
+
+    /// Parse Golly's "WireWorld" rule name, case insensitively.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().eq_ignore_ascii_case("WireWorld") {
+            Ok(WireWorldRule)
+        } else {
+            Err(RuleParseError::InvalidFormat)
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::str::FromStr;
+
+use wasm_bindgen::prelude::*;
+
+use crate::*;
+
+/******************************************************************************
+ * Schedule
+ *****************************************************************************/
+
+/// The order in which the cells of a universe are updated.
+///
+/// With the synchronous schedule, all cells are updated at once from the
+/// current generation. With the other schedules, cells are updated one at a
+/// time from the current state of their neighbours, which may already have
+/// been updated during the same tick.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Schedule {
+    /// All cells are updated at once.
+    #[default]
+    Synchronous,
+    /// All cells are updated once, in a new random order at each tick.
+    RandomSequential,
+    /// All cells are updated once, row by row from the top left corner.
+    Sweep,
+    /// Each cell is updated with the given probability, from the current
+    /// generation.
+    AlphaAsynchronous(f64),
+}
+
+impl std::fmt::Display for Schedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Schedule::Synchronous => write!(f, "synchronous"),
+            Schedule::RandomSequential => write!(f, "random-sequential"),
+            Schedule::Sweep => write!(f, "sweep"),
+            Schedule::AlphaAsynchronous(alpha) => write!(f, "alpha={}", alpha),
+        }
+    }
+}
+
+pub enum ScheduleParseError {
+    InvalidFormat,
+    InvalidAlpha,
+}
+
+impl std::fmt::Display for ScheduleParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ScheduleParseError::InvalidFormat => write!(f, "invalid schedule format"),
+            ScheduleParseError::InvalidAlpha => write!(f, "invalid alpha value"),
+        }
+    }
+}
+
+impl From<ScheduleParseError> for JsValue {
+    fn from(err: ScheduleParseError) -> Self {
+        JsValue::from_str(&err.to_string())
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = ScheduleParseError;
+
+    /// Parse "synchronous", "random-sequential", "sweep" or "alpha=α", α
+    /// being the probability of a cell to be updated, in the range (0, 1].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_lowercase();
+
+        if let Some(alpha) = s.strip_prefix("alpha=") {
+            return alpha
+                .trim()
+                .parse::<f64>().ok()
+                .filter(|&alpha| alpha > 0. && alpha <= 1.)
+                .map(Schedule::AlphaAsynchronous)
+                .ok_or(ScheduleParseError::InvalidAlpha);
+        }
+
+        match s.as_str() {
+            "synchronous" => Ok(Schedule::Synchronous),
+            "random-sequential" => Ok(Schedule::RandomSequential),
+            "sweep" => Ok(Schedule::Sweep),
+            _ => Err(ScheduleParseError::InvalidFormat),
+        }
+    }
+}
+
+/******************************************************************************
+ * Asynchronous update
+ *****************************************************************************/
+
+impl Universe {
+    /// Update the cells of the universe one at a time according to its
+    /// schedule.
+    ///
+    /// Returns false, leaving the universe untouched, if the schedule is
+    /// synchronous or if the rule cannot update cells one at a time (see
+    /// `Rule::next_cell`).
+    pub(crate) fn asynchronous_step(&mut self) -> bool {
+        if self.schedule == Schedule::Synchronous || !self.rule.has_next_cell() {
+            return false;
+        }
+
+        // cells are updated from their actual state
+        self.clear_background();
+
+        match self.schedule {
+            Schedule::Synchronous => (),
+            Schedule::RandomSequential => {
+                let mut order = (0..self.cells.len()).collect::<Vec<_>>();
+                for i in (1..order.len()).rev() {
+                    let j = self.random.next_below(i as u32 + 1) as usize;
+                    order.swap(i, j);
+                }
+                self.update_cells(order);
+            },
+            Schedule::Sweep => {
+                self.update_cells(0..self.cells.len());
+            },
+            Schedule::AlphaAsynchronous(alpha) => {
+                let mut cells = self.cells.clone();
+                for (idx, cell) in cells.iter_mut().enumerate() {
+                    if self.random.next_f64() < alpha {
+                        if let Some(next_cell) = self.next_cell(idx) {
+                            *cell = next_cell;
+                        }
+                    }
+                }
+                *self.cells = cells;
+            },
+        }
+
+        true
+    }
+
+    /// Compute the next state of the cell at `idx` alone, according to the
+    /// rule of its zone (see `add_zone`), or `None` if the cell is frozen or
+    /// if its rule cannot update cells one at a time.
+    fn next_cell(&self, idx: usize) -> Option<Cell> {
+        let (col, row) = self.get_coordinates(idx);
+        self.rule_at(idx)?.next_cell(self, col, row)
+    }
+
+    /// Update the cells of the given indices in turn.
+    fn update_cells(&mut self, order: impl IntoIterator<Item = usize>) {
+        for idx in order {
+            if let Some(next_cell) = self.next_cell(idx) {
+                self.cells[idx] = next_cell;
+            }
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+/// Get the update schedule of the universe.
+pub fn schedule(&self) -> String {
+    self.schedule.to_string()
+}
+
+/// Set the update schedule of the universe.
+///
+/// Supported schedules are:
+/// - "synchronous": all cells are updated at once, the default,
+/// - "random-sequential": all cells are updated once per tick, one at a time
+///   in a random order,
+/// - "sweep": all cells are updated once per tick, one at a time row by row,
+/// - "alpha=α" (e.g. "alpha=0.5"): each cell is updated with a probability of
+///   α per tick, from the current generation.
+///
+/// Asynchronous schedules use the random number generator of the universe
+/// (see `set_seed`). They apply to any rule except one-dimensional rules,
+/// which are always updated synchronously, as are universes in reversible
+/// mode.
+pub fn set_schedule(&mut self, schedule: &str) -> Result<(), ScheduleParseError> {
+    self.schedule = schedule.parse()?;
+    Ok(())
+}}
@@ -0,0 +1,329 @@
+use std::str::FromStr;
+
+use wasm_bindgen::prelude::*;
+
+use crate::*;
+
+/******************************************************************************
+ * Topology
+ *****************************************************************************/
+
+/// The topology of a universe, i.e. how its edges are joined, as in Golly's
+/// bounded grids.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Topology {
+    /// The edges of the universe wrap around, cells beyond an edge being the
+    /// cells of the opposite edge.
+    ///
+    /// Cells crossing the bottom edge of a torus may be shifted to the right
+    /// by `col_shift` columns, or cells crossing the right edge shifted down
+    /// by `row_shift` rows.
+    Torus { col_shift: i32, row_shift: i32 },
+    /// The edges of the universe wrap around, one pair of edges being
+    /// twisted: the columns are reversed when crossing the top and bottom
+    /// edges if `twisted_cols` is set, and the rows are reversed when
+    /// crossing the left and right edges otherwise.
+    KleinBottle { twisted_cols: bool },
+    /// The edges of the universe wrap around, both pairs of edges being
+    /// twisted.
+    CrossSurface,
+    /// The top edge of a square universe is joined to its left edge, and its
+    /// bottom edge to its right edge.
+    Sphere,
+    /// The universe is bounded, cells beyond its edges being permanently
+    /// dead.
+    Plane,
+    /// The universe is unbounded, its frame growing and shrinking to follow
+    /// its live cells (see `Universe::origin`).
+    Unbounded,
+}
+
+impl Default for Topology {
+    fn default() -> Self {
+        Topology::Torus { col_shift: 0, row_shift: 0 }
+    }
+}
+
+#[derive(Debug)]
+pub enum TopologyError {
+    InvalidTopology,
+    OutOfRange,
+}
+
+impl std::fmt::Display for TopologyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TopologyError::InvalidTopology => write!(f, "invalid topology"),
+            TopologyError::OutOfRange => write!(f, "cell out of range"),
+        }
+    }
+}
+
+impl From<TopologyError> for JsValue {
+    fn from(err: TopologyError) -> Self {
+        JsValue::from_str(&err.to_string())
+    }
+}
+
+/// Wrap a coordinate into `0..size`, also giving the number of times the
+/// coordinate crossed the edges, negative when crossing the low edge.
+fn wrap(value: i32, size: i32) -> (i32, i32) {
+    (value.rem_euclid(size), value.div_euclid(size))
+}
+
+impl Topology {
+    /// Map the coordinates (`col`, `row`) of a cell of a universe of the given
+    /// size to the coordinates of the cell they designate in the universe,
+    /// or `None` if there is no such cell.
+    pub fn map(&self, col: i32, row: i32, width: i32, height: i32) -> Option<(i32, i32)> {
+        let contains = |col: i32, row: i32| {
+            (0..width).contains(&col) && (0..height).contains(&row)
+        };
+
+        if contains(col, row) {
+            return Some((col, row));
+        }
+
+        match *self {
+            Topology::Torus { col_shift, row_shift } => {
+                let (row, crossings) = wrap(row, height);
+                let (col, crossings) = wrap(col + crossings*col_shift, width);
+                let (row, _) = wrap(row + crossings*row_shift, height);
+                Some((col, row))
+            },
+            Topology::KleinBottle { twisted_cols: true } => {
+                let (col, _) = wrap(col, width);
+                let (row, crossings) = wrap(row, height);
+                let col = if crossings%2 != 0 { width - 1 - col } else { col };
+                Some((col, row))
+            },
+            Topology::KleinBottle { twisted_cols: false } => {
+                let (row, _) = wrap(row, height);
+                let (col, crossings) = wrap(col, width);
+                let row = if crossings%2 != 0 { height - 1 - row } else { row };
+                Some((col, row))
+            },
+            Topology::CrossSurface => {
+                let (row, crossings) = wrap(row, height);
+                let col = if crossings%2 != 0 { width - 1 - col } else { col };
+                let (col, crossings) = wrap(col, width);
+                let row = if crossings%2 != 0 { height - 1 - row } else { row };
+                Some((col, row))
+            },
+            Topology::Sphere => {
+                // the k-th row above the top edge is the k-th column of the
+                // left edge, and so on, cells beyond the corners being dead
+                let (col, row) = match ((0..width).contains(&col), (0..height).contains(&row)) {
+                    (true, false) if row < 0 => (-row - 1, col),
+                    (true, false) => (2*height - 1 - row, col),
+                    (false, true) if col < 0 => (row, -col - 1),
+                    (false, true) => (row, 2*width - 1 - col),
+                    _ => return None,
+                };
+                Some((col, row)).filter(|&(col, row)| contains(col, row))
+            },
+            Topology::Plane | Topology::Unbounded => None,
+        }
+    }
+
+    /// Write the topology of a universe of the given size as a Golly bounded
+    /// grid, e.g. "T30,20", "T30+5,20", "K30*,20", "C30,20", "S30" or
+    /// "P30,20", or "P0,0" for an unbounded universe.
+    pub fn grid(&self, width: i32, height: i32) -> String {
+        let shift = |shift: i32| match shift {
+            0 => String::new(),
+            shift => format!("{:+}", shift),
+        };
+
+        match *self {
+            Topology::Torus { col_shift, row_shift } => {
+                format!("T{}{},{}{}", width, shift(col_shift), height, shift(row_shift))
+            },
+            Topology::KleinBottle { twisted_cols: true } => format!("K{}*,{}", width, height),
+            Topology::KleinBottle { twisted_cols: false } => format!("K{},{}*", width, height),
+            Topology::CrossSurface => format!("C{},{}", width, height),
+            Topology::Sphere => format!("S{}", width),
+            Topology::Plane => format!("P{},{}", width, height),
+            Topology::Unbounded => String::from("P0,0"),
+        }
+    }
+
+    /// Parse a Golly bounded grid, e.g. "T30,20", "T30+5,20", "K30*,20",
+    /// "C30,20", "S30" or "P30,20", giving the topology and the size of the
+    /// universe.
+    ///
+    /// The height is the width if omitted. Infinite dimensions, i.e. 0, are
+    /// only supported by planes infinite in both dimensions, i.e. "P0,0",
+    /// giving an unbounded universe of size 0.
+    pub fn parse_grid(s: &str) -> Result<(Topology, u32, u32), TopologyError> {
+        let s = s.trim();
+        let kind = s.chars().next().ok_or(TopologyError::InvalidTopology)?;
+
+        // parse a dimension, its twist and its shift, e.g. "30*+5"
+        let dimension = |s: &str| -> Result<(u32, bool, i32), TopologyError> {
+            let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+            let size = s[..end]
+                .parse::<u32>()
+                .or(Err(TopologyError::InvalidTopology))?;
+            let (twisted, shift) = match s[end..].strip_prefix('*') {
+                Some(shift) => (true, shift),
+                None => (false, &s[end..]),
+            };
+            let shift = match shift {
+                "" => 0,
+                shift if shift.starts_with(['+', '-']) => {
+                    shift.parse().or(Err(TopologyError::InvalidTopology))?
+                },
+                _ => return Err(TopologyError::InvalidTopology),
+            };
+            Ok((size, twisted, shift))
+        };
+
+        let dimensions = s[kind.len_utf8()..].split(',').collect::<Vec<_>>();
+        let (width, height) = match dimensions[..] {
+            [width] => (dimension(width)?, dimension(width)?),
+            [width, height] => (dimension(width)?, dimension(height)?),
+            _ => return Err(TopologyError::InvalidTopology),
+        };
+
+        let topology = match (kind.to_ascii_uppercase(), width, height) {
+            ('P', (0, false, 0), (0, false, 0)) => Topology::Unbounded,
+            (_, (0, _, _), _) | (_, _, (0, _, _)) => {
+                return Err(TopologyError::InvalidTopology);
+            },
+            ('T', (_, false, col_shift), (_, false, row_shift))
+                if col_shift == 0 || row_shift == 0 => {
+                Topology::Torus { col_shift, row_shift }
+            },
+            ('K', (_, twisted_cols, 0), (_, twisted_rows, 0))
+                if twisted_cols != twisted_rows => {
+                Topology::KleinBottle { twisted_cols }
+            },
+            ('C', (_, false, 0), (_, false, 0)) => Topology::CrossSurface,
+            ('S', (width, false, 0), (height, false, 0)) if width == height => {
+                Topology::Sphere
+            },
+            ('P', (_, false, 0), (_, false, 0)) => Topology::Plane,
+            _ => return Err(TopologyError::InvalidTopology),
+        };
+
+        Ok((topology, width.0, height.0))
+    }
+}
+
+impl FromStr for Topology {
+    type Err = TopologyError;
+
+    /// Parse the name of a topology, i.e. "torus", "klein-bottle",
+    /// "cross-surface", "sphere", "plane" or "unbounded", case insensitively.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "torus" => Ok(Topology::default()),
+            "klein-bottle" => Ok(Topology::KleinBottle { twisted_cols: true }),
+            "cross-surface" => Ok(Topology::CrossSurface),
+            "sphere" => Ok(Topology::Sphere),
+            "plane" => Ok(Topology::Plane),
+            "unbounded" => Ok(Topology::Unbounded),
+            _ => Err(TopologyError::InvalidTopology),
+        }
+    }
+}
+
+/******************************************************************************
+ * Universe
+ *****************************************************************************/
+
+impl Universe {
+    /// Get the index of the cell at (`col`, `row`) following the topology of
+    /// the universe, or `None` if there is no such cell, e.g. beyond the
+    /// edges of a bounded universe.
+    pub(crate) fn neighbour_index(&self, col: i32, row: i32) -> Option<usize> {
+        self.topology
+            .map(col, row, self.width, self.height)
+            .map(|(col, row)| (row*self.width + col) as usize)
+    }
+
+    /// Get the cell at (`col`, `row`) following the topology of the universe,
+    /// cells beyond the edges of a bounded universe being dead.
+    ///
+    /// As every cell, the cell is given relative to the background of the
+    /// universe (see `Universe::background`): cells beyond the frame of an
+    /// unbounded universe are in the state of its background.
+    pub(crate) fn neighbour(&self, col: i32, row: i32) -> Cell {
+        match self.neighbour_index(col, row) {
+            Some(idx) => self.cells[idx],
+            None if self.topology == Topology::Unbounded => Cell::Dead,
+            None => self.background,
+        }
+    }
+
+    /// Get the index of the cell at (`col`, `row`) for an access from outside
+    /// of the universe, coordinates beyond the edges of a bounded universe
+    /// being an error.
+    pub(crate) fn checked_index(&self, col: i32, row: i32) -> Result<usize, TopologyError> {
+        self.neighbour_index(col, row).ok_or(TopologyError::OutOfRange)
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+/// Get the topology of the universe as a Golly bounded grid (see
+/// `set_topology`), e.g. "T30,20" for a torus of 30 by 20 cells.
+pub fn topology(&self) -> String {
+    self.topology.grid(self.width, self.height)
+}
+
+/// Set the topology of the universe.
+///
+/// Accepts the Golly bounded grids, resizing the universe to their size:
+/// "T30,20" for a torus, the default, whose edges wrap around, "T30+5,20"
+/// for a torus whose cells crossing the bottom edge are shifted by 5 columns
+/// (or "T30,20+5" for a shift of the rows at the right edge), "K30*,20" for
+/// a Klein bottle whose top and bottom edges are twisted (or "K30,20*" for
+/// its left and right edges), "C30,20" for a cross-surface, "S30" for a
+/// sphere whose top and bottom edges are joined to its left and right edges,
+/// and "P30,20" for a plane.
+///
+/// "P0,0" gives an unbounded universe, whose frame grows and shrinks to
+/// follow its live cells (see `origin`), and whose zones are removed (see
+/// `add_zone`).
+///
+/// The names "torus", "klein-bottle", "cross-surface", "sphere", "plane" and
+/// "unbounded" are accepted too, keeping the size of the universe.
+///
+/// On a plane, cells beyond the edges are permanently dead, patterns
+/// translated beyond the edges are cut, turmites moving beyond the edges are
+/// removed, and getting or setting cells beyond the edges is an error.
+pub fn set_topology(&mut self, topology: &str) -> Result<(), TopologyError> {
+    let topology = match Topology::parse_grid(topology) {
+        Ok((Topology::Unbounded, _, _)) => Topology::Unbounded,
+        Ok((topology, width, height)) => {
+            let (width, height) = (width as i32, height as i32);
+            if !self.rule.fits(width, height) {
+                return Err(TopologyError::InvalidTopology);
+            }
+
+            if width != self.width || height != self.height {
+                self.reframe(0, 0, width, height);
+                self.clear_previous();
+            }
+            topology
+        },
+        Err(_) => match topology.parse()? {
+            Topology::Sphere if self.width != self.height => {
+                return Err(TopologyError::InvalidTopology);
+            },
+            topology => topology,
+        },
+    };
+
+    // bounded universes have their origin at their top left cell, unbounded
+    // universes have no zones
+    if topology != Topology::Unbounded {
+        self.origin = (0, 0);
+    } else {
+        self.clear_zones();
+    }
+    self.topology = topology;
+    Ok(())
+}}
@@ -0,0 +1,510 @@
+use std::str::FromStr;
+
+use wasm_bindgen::prelude::*;
+
+use crate::*;
+
+/******************************************************************************
+ * Turmite
+ *****************************************************************************/
+
+/// A turmite, i.e. an agent moving on the grid of a universe, reading and
+/// writing the cells it walks on.
+///
+/// The heading of a turmite is 0 for north, 1 for east, 2 for south and 3 for
+/// west.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Turmite {
+    col: i32,
+    row: i32,
+    heading: u8,
+    state: u8,
+}
+
+impl Turmite {
+    /// Create a turmite in state 0.
+    pub fn new(col: i32, row: i32, heading: u8) -> Self {
+        Turmite { col, row, heading: heading%4, state: 0 }
+    }
+
+    /// Get the column and the row of the turmite.
+    pub fn position(&self) -> (i32, i32) {
+        (self.col, self.row)
+    }
+
+    /// Get the heading of the turmite.
+    pub fn heading(&self) -> u8 {
+        self.heading
+    }
+
+    /// Get the internal state of the turmite.
+    pub fn state(&self) -> u8 {
+        self.state
+    }
+
+    /// Get the column and row offsets of a move forward.
+    fn forward(&self) -> (i32, i32) {
+        match self.heading {
+            0 => (0, -1),
+            1 => (1, 0),
+            2 => (0, 1),
+            _ => (-1, 0),
+        }
+    }
+}
+
+/******************************************************************************
+ * Rule
+ *****************************************************************************/
+
+/// A turn of a turmite, numbered as in Golly's turmite notation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Turn {
+    None = 1,
+    Right = 2,
+    UTurn = 4,
+    Left = 8,
+}
+
+impl Turn {
+    /// Get the number of quarter turns to the right of the turn.
+    fn quarters(&self) -> u8 {
+        match self {
+            Turn::None => 0,
+            Turn::Right => 1,
+            Turn::UTurn => 2,
+            Turn::Left => 3,
+        }
+    }
+
+    /// Get the letter of the turn in Langton's ant notation.
+    fn letter(&self) -> char {
+        match self {
+            Turn::None => 'N',
+            Turn::Right => 'R',
+            Turn::UTurn => 'U',
+            Turn::Left => 'L',
+        }
+    }
+}
+
+/// The color written, the turn made and the next state of a turmite, given
+/// its state and the color of the cell it stands on.
+type TurmiteTransition = (u8, Turn, u8);
+
+/// A turmite rule.
+///
+/// A turmite reads the color of the cell it stands on, i.e. its state, then
+/// writes a new color, turns, moves forward one cell and takes a new internal
+/// state, all given by the transition of its state and the color read. Cells
+/// in states above the colors of the rule are read as color 0.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TurmiteRule {
+    colors: u16,
+    states: u16,
+    transitions: Vec<TurmiteTransition>,
+}
+
+impl Default for TurmiteRule {
+    /// Langton's ant, "RL".
+    fn default() -> Self {
+        TurmiteRule {
+            colors: 2,
+            states: 1,
+            transitions: vec![(1, Turn::Right, 0), (0, Turn::Left, 0)],
+        }
+    }
+}
+
+impl TurmiteRule {
+    /// Get the number of colors of the rule.
+    pub fn colors(&self) -> u16 {
+        self.colors
+    }
+
+    /// Get the number of internal states of the rule.
+    pub fn states(&self) -> u16 {
+        self.states
+    }
+
+    fn transition(&self, state: u8, color: u8) -> TurmiteTransition {
+        let color = if (color as u16) < self.colors { color } else { 0 };
+        self.transitions[state as usize*self.colors as usize + color as usize]
+    }
+
+    /// Check if the rule is a Langton's ant rule, i.e. a single state rule
+    /// cycling through the colors.
+    fn is_ant(&self) -> bool {
+        self.states == 1 && self.transitions
+            .iter()
+            .enumerate()
+            .all(|(color, &(write, _, _))| write as u16 == (color as u16 + 1)%self.colors)
+    }
+}
+
+/******************************************************************************
+ * Encoder
+ *****************************************************************************/
+
+impl std::fmt::Display for TurmiteRule {
+    /// Write Langton's ant rules as a string of turns (e.g. "RL"), and other
+    /// rules in Golly's turmite notation (e.g. "{{{1,2,0},{0,8,0}}}").
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.is_ant() {
+            return self.transitions
+                .iter()
+                .try_for_each(|(_, turn, _)| write!(f, "{}", turn.letter()));
+        }
+
+        let states = self.transitions
+            .chunks(self.colors as usize)
+            .map(|transitions| {
+                let transitions = transitions
+                    .iter()
+                    .map(|&(write, turn, next)| format!("{{{},{},{}}}", write, turn as u8, next))
+                    .collect::<Vec<_>>();
+                format!("{{{}}}", transitions.join(","))
+            })
+            .collect::<Vec<_>>();
+
+        write!(f, "{{{}}}", states.join(","))
+    }
+}
+
+/******************************************************************************
+ * Decoder
+ *****************************************************************************/
+
+fn parse_turn(value: u32) -> Result<Turn, RuleParseError> {
+    match value {
+        1 => Ok(Turn::None),
+        2 => Ok(Turn::Right),
+        4 => Ok(Turn::UTurn),
+        8 => Ok(Turn::Left),
+        _ => Err(RuleParseError::InvalidTransition),
+    }
+}
+
+/// Parse Langton's ant notation, one turn letter per color.
+fn parse_ant(s: &str) -> Result<TurmiteRule, RuleParseError> {
+    let turns = s
+        .chars()
+        .map(|letter| match letter.to_ascii_uppercase() {
+            'N' => Ok(Turn::None),
+            'R' => Ok(Turn::Right),
+            'U' => Ok(Turn::UTurn),
+            'L' => Ok(Turn::Left),
+            _ => Err(RuleParseError::InvalidLetter),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if !(2..=256).contains(&turns.len()) {
+        return Err(RuleParseError::InvalidStates);
+    }
+
+    let colors = turns.len() as u16;
+
+    Ok(TurmiteRule {
+        colors,
+        states: 1,
+        transitions: turns
+            .into_iter()
+            .enumerate()
+            .map(|(color, turn)| (((color as u16 + 1)%colors) as u8, turn, 0))
+            .collect(),
+    })
+}
+
+/// Parse Golly's turmite notation, a list of states, each being a list of
+/// transitions by color.
+fn parse_table(s: &str) -> Result<TurmiteRule, RuleParseError> {
+    let mut states = Vec::<Vec<Vec<u32>>>::new();
+    let mut depth = 0;
+    let mut number = String::new();
+
+    for c in s.chars().filter(|c| !c.is_whitespace()) {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        if !number.is_empty() {
+            if depth != 3 {
+                return Err(RuleParseError::InvalidFormat);
+            }
+
+            let value = number.parse().or(Err(RuleParseError::InvalidTransition))?;
+            states.last_mut().unwrap().last_mut().unwrap().push(value);
+            number.clear();
+        }
+
+        match (c, depth) {
+            ('{', 0) if states.is_empty() => (),
+            ('{', 1) => states.push(Vec::new()),
+            ('{', 2) => states.last_mut().unwrap().push(Vec::new()),
+            ('}', 1..=3) | (',', 1..=3) => (),
+            _ => return Err(RuleParseError::InvalidFormat),
+        }
+
+        depth = match c {
+            '{' => depth + 1,
+            '}' => depth - 1,
+            _ => depth,
+        };
+    }
+
+    if depth != 0 || !number.is_empty() || states.is_empty() {
+        return Err(RuleParseError::InvalidFormat);
+    }
+
+    let colors = states[0].len();
+    if !(2..=256).contains(&colors) || states.len() > 256
+        || states.iter().any(|state| state.len() != colors) {
+        return Err(RuleParseError::InvalidStates);
+    }
+
+    let transitions = states
+        .iter()
+        .flatten()
+        .map(|transition| match transition[..] {
+            [write, turn, next] if (write as usize) < colors && (next as usize) < states.len() => {
+                Ok((write as u8, parse_turn(turn)?, next as u8))
+            },
+            _ => Err(RuleParseError::InvalidTransition),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(TurmiteRule {
+        colors: colors as u16,
+        states: states.len() as u16,
+        transitions,
+    })
+}
+
+impl FromStr for TurmiteRule {
+    type Err = RuleParseError;
+
+    /// Parse a rule either in Langton's ant notation, one turn per color
+    /// among "L" (left), "R" (right), "N" (no turn) and "U" (u-turn), e.g.
+    /// "RL" for Langton's ant, or in Golly's turmite notation, e.g.
+    /// "{{{1,2,0},{0,8,0}}}" for Langton's ant.
+    ///
+    /// In Golly's notation, each state gives, for each color, the color to
+    /// write, the turn (1 for no turn, 2 for right, 4 for u-turn and 8 for
+    /// left) and the next state.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if s.starts_with('{') {
+            parse_table(s)
+        } else {
+            parse_ant(s)
+        }
+    }
+}
+
+/******************************************************************************
+ * Universe
+ *****************************************************************************/
+
+impl Universe {
+    /// Move the turmites of the universe, one after the other.
+    pub(crate) fn step_turmites(&mut self) {
+        if self.turmites.is_empty() {
+            return;
+        }
+
+        // turmites read and write the actual state of the cells
+        self.clear_background();
+
+        let mut gone = Vec::new();
+
+        for i in 0..self.turmites.len() {
+            let mut turmite = self.turmites[i];
+            let idx = self.get_index(turmite.col, turmite.row);
+            let (write, turn, next) = self.turmite_rule.transition(
+                turmite.state,
+                self.cells[idx].state(),
+            );
+
+            self.cells[idx] = Cell::from(write);
+            turmite.heading = (turmite.heading + turn.quarters())%4;
+            turmite.state = next;
+
+            let (delta_col, delta_row) = turmite.forward();
+            match self.neighbour_index(turmite.col + delta_col, turmite.row + delta_row) {
+                Some(idx) => {
+                    let (col, row) = self.get_coordinates(idx);
+                    turmite.col = col;
+                    turmite.row = row;
+                },
+                // turmites moving beyond the edges of a bounded universe
+                None => gone.push(i),
+            }
+
+            self.turmites[i] = turmite;
+        }
+
+        for i in gone.into_iter().rev() {
+            self.turmites.remove(i);
+        }
+    }
+
+    /// Translate the turmites of the universe by (`dx`, `dy`), turmites
+    /// translated beyond the edges of a bounded universe being removed.
+    pub(crate) fn translate_turmites(&mut self, dx: i32, dy: i32) {
+        let turmites = std::mem::take(&mut self.turmites);
+
+        self.turmites = turmites
+            .into_iter()
+            .filter_map(|mut turmite| {
+                let (col, row) = turmite.position();
+                let idx = self.neighbour_index(col + dx, row + dy)?;
+                let (col, row) = self.get_coordinates(idx);
+                turmite.col = col;
+                turmite.row = row;
+                Some(turmite)
+            })
+            .collect();
+    }
+
+    /// Move the turmites of the universe to a new frame of the universe, given
+    /// by its left, top, width and height, removing those out of the frame.
+    pub(crate) fn reframe_turmites(&mut self, (left, top, width, height): (i32, i32, i32, i32)) {
+        self.turmites.retain_mut(|turmite| {
+            turmite.col -= left;
+            turmite.row -= top;
+            (0..width).contains(&turmite.col) && (0..height).contains(&turmite.row)
+        });
+    }
+
+    /// Get the RLE comment lines describing the turmites of the universe,
+    /// i.e. a "#T" line giving the turmite rule and one "#A" line giving the
+    /// column, row, heading and state of each turmite.
+    pub(crate) fn turmite_comments(&self) -> String {
+        if self.turmites.is_empty() {
+            return String::new();
+        }
+
+        let mut comments = format!("#T {}\n", self.turmite_rule);
+        for turmite in self.turmites.iter() {
+            comments.push_str(&format!("#A {} {} {} {}\n",
+                turmite.col, turmite.row, turmite.heading, turmite.state,
+            ));
+        }
+        comments
+    }
+
+    /// Read the turmites of the universe from RLE comment lines, as written
+    /// by `turmite_comments`, ignoring other comments.
+    ///
+    /// Positions are checked like those of `add_turmite`, positions beyond the
+    /// edges of the universe being an error.
+    pub(crate) fn read_turmite_comments<'a>(
+        &mut self,
+        lines: impl Iterator<Item = &'a str>,
+    ) -> Result<(), RLEDecoderError> {
+        for line in lines {
+            if let Some(rule) = line.strip_prefix("#T") {
+                self.turmite_rule = rule.parse()
+                    .or(Err(RLEDecoderError::InvalidTurmite))?;
+            } else if let Some(turmite) = line.strip_prefix("#A") {
+                let values = turmite
+                    .split_whitespace()
+                    .map(|value| value.parse::<i32>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .or(Err(RLEDecoderError::InvalidTurmite))?;
+
+                match values[..] {
+                    [col, row, heading, state] if (0..4).contains(&heading)
+                        && (0..self.turmite_rule.states() as i32).contains(&state) => {
+                        let idx = self.checked_index(col, row)
+                            .or(Err(RLEDecoderError::InvalidPosition))?;
+                        let (col, row) = self.get_coordinates(idx);
+                        let mut turmite = Turmite::new(col, row, heading as u8);
+                        turmite.state = state as u8;
+                        self.turmites.push(turmite);
+                    },
+                    _ => return Err(RLEDecoderError::InvalidTurmite),
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+/// Get the rule of the turmites of the universe.
+pub fn turmite_rule(&self) -> String {
+    self.turmite_rule.to_string()
+}
+
+/// Set the rule of the turmites of the universe.
+///
+/// Accepts Langton's ant notation, one turn per color among "L" (left), "R"
+/// (right), "N" (no turn) and "U" (u-turn), e.g. "RL" for Langton's ant, the
+/// default, or "LLRR", and Golly's turmite notation giving, for each internal
+/// state and each color, the color to write, the turn (1 for no turn, 2 for
+/// right, 4 for u-turn and 8 for left) and the next internal state, e.g.
+/// "{{{1,2,1},{0,8,0}},{{1,2,1},{0,1,0}}}".
+///
+/// Turmites in states the rule does not have are reset to state 0.
+pub fn set_turmite_rule(&mut self, rule: &str) -> Result<(), RuleParseError> {
+    self.turmite_rule = rule.parse()?;
+
+    let states = self.turmite_rule.states();
+    for turmite in self.turmites.iter_mut() {
+        if turmite.state as u16 >= states {
+            turmite.state = 0;
+        }
+    }
+    Ok(())
+}
+
+/// Add a turmite in state 0 at (`col`, `row`), heading north (0), east (1),
+/// south (2) or west (3).
+///
+/// Turmites move once per `tick`, after the cells are updated by the rule of
+/// the universe, one after the other in the order they were added.
+///
+/// Coordinates (see `origin`) beyond the edges of a bounded universe (see
+/// `set_topology`) are an error, and the frame of an unbounded universe grows
+/// to include the turmite.
+pub fn add_turmite(&mut self, col: i32, row: i32, heading: u8) -> Result<(), TopologyError> {
+    let idx = self.cell_index_mut(col, row)?;
+    let (col, row) = self.get_coordinates(idx);
+    self.turmites.push(Turmite::new(col, row, heading));
+    Ok(())
+}
+
+/// Remove all the turmites of the universe.
+pub fn clear_turmites(&mut self) {
+    self.turmites.clear();
+}
+
+/// Get the turmites of the universe, as a flat array of their column, row
+/// (see `origin`), heading and state.
+pub fn turmites(&self) -> Vec<i32> {
+    self.turmites
+        .iter()
+        .flat_map(|turmite| [
+            turmite.col + self.origin.0,
+            turmite.row + self.origin.1,
+            turmite.heading as i32,
+            turmite.state as i32,
+        ])
+        .collect()
+}
+
+/// Check if `tick` only moves the turmites of the universe.
+pub fn turmites_only(&self) -> bool {
+    self.turmites_only
+}
+
+/// Set whether `tick` only moves the turmites of the universe, the cells not
+/// being updated by the rule of the universe.
+pub fn set_turmites_only(&mut self, turmites_only: bool) {
+    self.turmites_only = turmites_only;
+}}
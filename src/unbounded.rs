@@ -0,0 +1,154 @@
+use wasm_bindgen::prelude::*;
+
+use crate::*;
+
+/******************************************************************************
+ * Frame
+ *****************************************************************************/
+
+/// The size of the chunks of an unbounded universe, its frame being always
+/// made of whole chunks.
+const CHUNK_SIZE: i32 = 16;
+
+/// Round a coordinate down to the first coordinate of its chunk.
+fn chunk_floor(value: i32) -> i32 {
+    value.div_euclid(CHUNK_SIZE)*CHUNK_SIZE
+}
+
+/// Round a coordinate up to the first coordinate of a chunk.
+fn chunk_ceil(value: i32) -> i32 {
+    chunk_floor(value + CHUNK_SIZE - 1)
+}
+
+impl Universe {
+    /// Get the bounds of the cells of the universe which are not dead, i.e.
+    /// the first column, the first row, the last column and the last row of
+    /// their bounding box, in the current or the previous generation, or
+    /// under a turmite.
+    fn live_bounds(&self) -> Option<(i32, i32, i32, i32)> {
+        // the cells of the previous generation are indexed after the current
+        // ones, `get_coordinates` wrapping their index
+        let previous = self.previous.as_deref().unwrap_or(&[]);
+        let cells = self.cells
+            .iter()
+            .chain(previous.iter())
+            .enumerate()
+            .filter(|(_, &cell)| cell != Cell::Dead)
+            .map(|(idx, _)| self.get_coordinates(idx));
+        let turmites = self.turmites.iter().map(Turmite::position);
+
+        cells.chain(turmites).fold(None, |bounds, (col, row)| match bounds {
+            None => Some((col, row, col, row)),
+            Some((left, top, right, bottom)) => Some((
+                left.min(col),
+                top.min(row),
+                right.max(col),
+                bottom.max(row),
+            )),
+        })
+    }
+
+    /// Move the frame of an unbounded universe to the chunks covering its
+    /// cells which are not dead, with a margin of the range of its rule, so
+    /// that the frame grows and shrinks to follow the pattern.
+    ///
+    /// The frame of an empty universe is kept.
+    pub(crate) fn fit_frame(&mut self) {
+        if self.topology != Topology::Unbounded {
+            return;
+        }
+
+        let (left, top, right, bottom) = match self.live_bounds() {
+            Some(bounds) => bounds,
+            None => return,
+        };
+        let margin = self.rule.range() as i32;
+        let (origin_col, origin_row) = self.origin;
+
+        let left = chunk_floor(origin_col + left - margin) - origin_col;
+        let top = chunk_floor(origin_row + top - margin) - origin_row;
+        let right = chunk_ceil(origin_col + right + margin + 1) - origin_col;
+        let bottom = chunk_ceil(origin_row + bottom + margin + 1) - origin_row;
+
+        if (left, top, right, bottom) != (0, 0, self.width, self.height) {
+            self.reframe(left, top, right - left, bottom - top);
+        }
+    }
+
+    /// Get the index of the cell at (`col`, `row`) to change its state from
+    /// outside of the universe (see `Universe::origin`).
+    ///
+    /// The frame of an unbounded universe grows to the chunks including the
+    /// cell, and coordinates beyond the edges of a bounded universe are an
+    /// error.
+    pub(crate) fn cell_index_mut(&mut self, col: i32, row: i32) -> Result<usize, TopologyError> {
+        let (origin_col, origin_row) = self.origin;
+
+        if self.topology == Topology::Unbounded {
+            let left = chunk_floor(col).min(origin_col) - origin_col;
+            let top = chunk_floor(row).min(origin_row) - origin_row;
+            let right = chunk_ceil(col + 1).max(origin_col + self.width) - origin_col;
+            let bottom = chunk_ceil(row + 1).max(origin_row + self.height) - origin_row;
+
+            if (left, top, right, bottom) != (0, 0, self.width, self.height) {
+                self.reframe(left, top, right - left, bottom - top);
+            }
+        }
+
+        self.checked_index(col - self.origin.0, row - self.origin.1)
+    }
+
+    /// Get the rectangle of the universe to render, i.e. its viewport (see
+    /// `set_viewport`) or its whole frame, as its left column, top row, width
+    /// and height, relative to the frame of the universe.
+    pub(crate) fn render_frame(&self) -> (i32, i32, i32, i32) {
+        match self.viewport {
+            Some((col, row, width, height)) => {
+                (col - self.origin.0, row - self.origin.1, width, height)
+            },
+            None => (0, 0, self.width, self.height),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+/// Get the coordinates of the top left cell of the frame of the universe,
+/// i.e. of the first cell of `cells`, as an array [col, row].
+///
+/// The frame of a bounded universe is the universe itself, whose origin is
+/// [0, 0]. The frame of an unbounded universe (see `set_topology`) is made of
+/// chunks of 16 by 16 cells, and moves before each generation to the chunks
+/// covering its pattern, its origin moving with it so that cells keep their
+/// coordinates.
+///
+/// Cells are got and set (e.g. with `get_cell` and `set_cell`), and rendered
+/// through the viewport (see `set_viewport`), with their coordinates, setting
+/// a cell beyond the frame of an unbounded universe growing the frame.
+/// Turmites are given with their coordinates too, and unbounded universes
+/// have no zones (see `add_zone`).
+pub fn origin(&self) -> Vec<i32> {
+    vec![self.origin.0, self.origin.1]
+}
+
+/// Get the rectangle of the universe rendered by `render_to_context` and
+/// `render_to_string`, i.e. the viewport or the frame of the universe (see
+/// `origin`), as an array [col, row, width, height].
+pub fn viewport(&self) -> Vec<i32> {
+    let (col, row, width, height) = self.render_frame();
+    vec![col + self.origin.0, row + self.origin.1, width, height]
+}
+
+/// Render the rectangle of `width` by `height` cells whose top left cell is
+/// at (`col`, `row`) instead of the frame of the universe (see `origin`).
+///
+/// Cells of the viewport beyond the edges of the universe follow its
+/// topology, e.g. a torus is rendered repeatedly.
+pub fn set_viewport(&mut self, col: i32, row: i32, width: u32, height: u32) {
+    self.viewport = Some((col, row, 1.max(width as i32), 1.max(height as i32)));
+}
+
+/// Render the frame of the universe again.
+pub fn clear_viewport(&mut self) {
+    self.viewport = None;
+}}
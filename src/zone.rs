@@ -0,0 +1,246 @@
+use wasm_bindgen::prelude::*;
+
+use crate::*;
+
+/******************************************************************************
+ * Zones
+ *****************************************************************************/
+
+/// The maximum number of zones of a universe, zone 0 included.
+const MAX_ZONES: usize = 256;
+
+pub enum ZoneError {
+    InvalidZone,
+    InvalidRule,
+    TooManyZones,
+    UnboundedUniverse,
+}
+
+impl std::fmt::Display for ZoneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ZoneError::InvalidZone => write!(f, "invalid zone"),
+            ZoneError::InvalidRule => write!(f, "invalid rule"),
+            ZoneError::TooManyZones => write!(f, "too many zones"),
+            ZoneError::UnboundedUniverse => write!(f, "unbounded universes have no zones"),
+        }
+    }
+}
+
+impl From<ZoneError> for JsValue {
+    fn from(err: ZoneError) -> Self {
+        JsValue::from_str(&err.to_string())
+    }
+}
+
+/// Parse the rule of a zone, "frozen" (case insensitively) giving `None`.
+fn parse_zone_rule(rule: &str) -> Result<Option<Rule>, ZoneError> {
+    if rule.trim().eq_ignore_ascii_case("frozen") {
+        Ok(None)
+    } else {
+        rule.parse().map(Some).or(Err(ZoneError::InvalidRule))
+    }
+}
+
+impl Universe {
+    /// Get the rule of the cell at `idx`, or `None` if the cell is frozen.
+    pub(crate) fn rule_at(&self, idx: usize) -> Option<&Rule> {
+        match self.zones.as_ref().map_or(0, |zones| zones[idx]) {
+            0 => Some(&self.rule),
+            zone => self.zone_rules[zone as usize - 1].as_ref(),
+        }
+    }
+
+    /// Compute the next generation of the universe by a rule, with the actual
+    /// state of the cells, the background of the universe being dead.
+    fn actual_generation(&self, rule: &Rule) -> Vec<Cell> {
+        // rules containing B0 give the next generation relative to their
+        // next background
+        let background = rule.next_background(self);
+        rule.next_generation(self)
+            .into_iter()
+            .map(|cell| Cell::from(cell.state() ^ background.state()))
+            .collect()
+    }
+
+    /// Compute the next generation of a universe with zones, each cell being
+    /// updated once by the rule of its zone (see `Rule::next_cell`).
+    ///
+    /// The background of the universe must be dead, and the next generation
+    /// is given with the actual state of the cells, with or without zones.
+    /// Rules computing whole rows at once compute the next generation of the
+    /// universe once per zone.
+    pub(crate) fn zoned_generation(&self) -> Vec<Cell> {
+        let zones = match self.zones.as_ref() {
+            Some(zones) => zones,
+            None => return self.actual_generation(&self.rule),
+        };
+
+        let mut generations: Vec<Option<Vec<Cell>>> = vec![None; MAX_ZONES];
+
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(idx, &cell)| {
+                let rule = match self.rule_at(idx) {
+                    Some(rule) => rule,
+                    None => return cell,
+                };
+                let (col, row) = self.get_coordinates(idx);
+
+                rule.next_cell(self, col, row).unwrap_or_else(|| {
+                    let zone = zones[idx] as usize;
+                    let next = generations[zone]
+                        .get_or_insert_with(|| self.actual_generation(rule));
+                    next[idx]
+                })
+            })
+            .collect()
+    }
+
+    /// Check that the universe may have zones, i.e. that it is bounded, as
+    /// the frame of an unbounded universe moves with its pattern.
+    fn check_bounded(&self) -> Result<(), ZoneError> {
+        if self.topology == Topology::Unbounded {
+            Err(ZoneError::UnboundedUniverse)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_zone(&self, zone: u8) -> Result<(), ZoneError> {
+        if zone as usize <= self.zone_rules.len() {
+            Ok(())
+        } else {
+            Err(ZoneError::InvalidZone)
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+/// Add a zone to the universe, returning its index.
+///
+/// Zone 0 is the zone of every cell when the universe is created, and has
+/// the rule of the universe (see `set_rule`). Other zones have their own
+/// rule, accepting any notation supported by `set_rule`, or "frozen" for
+/// cells that never change. Cells are put in a zone with `paint_zone`,
+/// `set_zone` or `set_zone_cells`.
+///
+/// Rules with more states than the rule of the universe have their extra
+/// states rendered with the color of its last state. Rules containing B0 are
+/// computed with the actual state of the cells (see `Universe::background`)
+/// as soon as a zone is painted.
+///
+/// Unbounded universes (see `set_topology`) have no zones.
+pub fn add_zone(&mut self, rule: &str) -> Result<u8, ZoneError> {
+    self.check_bounded()?;
+
+    // zones are numbered from 1 to 255
+    if self.zone_rules.len() >= MAX_ZONES - 1 {
+        return Err(ZoneError::TooManyZones);
+    }
+
+    self.zone_rules.push(parse_zone_rule(rule)?);
+    Ok(self.zone_rules.len() as u8)
+}
+
+/// Get the rule of a zone, "frozen" for frozen zones, or `undefined` if the
+/// zone does not exist.
+pub fn zone_rule(&self, zone: u8) -> Option<String> {
+    match zone {
+        0 => Some(self.rule.to_string()),
+        zone => self.zone_rules
+            .get(zone as usize - 1)
+            .map(|rule| rule.as_ref().map_or(String::from("frozen"), Rule::to_string)),
+    }
+}
+
+/// Set the rule of a zone other than zone 0, whose rule is the rule of the
+/// universe.
+pub fn set_zone_rule(&mut self, zone: u8, rule: &str) -> Result<(), ZoneError> {
+    if zone == 0 {
+        return Err(ZoneError::InvalidZone);
+    }
+    self.check_zone(zone)?;
+
+    self.zone_rules[zone as usize - 1] = parse_zone_rule(rule)?;
+    Ok(())
+}
+
+/// Get the zone of a cell in the universe.
+///
+/// Coordinates beyond the edges of a bounded universe (see `set_topology`)
+/// are an error. Every cell of an unbounded universe is in zone 0.
+pub fn get_zone(&self, col: i32, row: i32) -> Result<u8, TopologyError> {
+    if self.topology == Topology::Unbounded {
+        return Ok(0);
+    }
+
+    let idx = self.checked_index(col, row)?;
+    Ok(self.zones.as_ref().map_or(0, |zones| zones[idx]))
+}
+
+/// Put a cell of the universe in a zone.
+pub fn set_zone(&mut self, col: i32, row: i32, zone: u8) -> Result<(), ZoneError> {
+    self.paint_zone(col, row, 1, 1, zone)
+}
+
+/// Put the cells of a rectangle of the universe, from (`col`, `row`) to
+/// (`col + width - 1`, `row + height - 1`), in a zone.
+///
+/// The rectangle wraps around the edges of a torus, and is cut at the edges
+/// of a bounded universe (see `set_topology`). Unbounded universes have no
+/// zones.
+pub fn paint_zone(
+    &mut self,
+    col: i32,
+    row: i32,
+    width: i32,
+    height: i32,
+    zone: u8,
+) -> Result<(), ZoneError> {
+    self.check_bounded()?;
+    self.check_zone(zone)?;
+
+    // cells are updated with the actual state of their neighbours
+    self.clear_background();
+
+    let len = self.cells.len();
+    let mut zones = self.zones.take().unwrap_or_else(|| vec![0; len]);
+    for delta_row in 0..height.min(self.height) {
+        for delta_col in 0..width.min(self.width) {
+            if let Some(idx) = self.neighbour_index(col + delta_col, row + delta_row) {
+                zones[idx] = zone;
+            }
+        }
+    }
+    self.zones = Some(zones);
+    Ok(())
+}
+
+/// Put a list of cells of the universe, e.g. a mask, in a zone.
+///
+/// Expects an array of arrays of the form [[row, col], [row, col], ...]
+pub fn set_zone_cells(
+    &mut self,
+    cells: js_sys::Array,
+    zone: u8,
+) -> Result<(), JsError> {
+    cells
+        .iter()
+        .try_for_each(|value| {
+            if value.is_array() {
+                let cell = value.unchecked_into::<js_sys::Array>();
+                let (col, row) = js_array_to_coordinate_tuple(&cell)?;
+                self.set_zone(col, row, zone)
+                    .map_err(|err| JsError::new(&err.to_string()))
+            } else { Err(JsError::new("Invalid type")) }
+        })
+}
+
+/// Remove all the zones of the universe, every cell being back in zone 0.
+pub fn clear_zones(&mut self) {
+    self.zones = None;
+    self.zone_rules.clear();
+}}
@@ -18,3 +18,925 @@ pub fn test_new() {
     assert_eq!(universe.height(), 3);
     assert_eq!(universe.get_cells(), &[Cell::Dead; 9]);
 }
+
+#[wasm_bindgen_test]
+pub fn test_rule() {
+    let mut universe = Universe::new(5, 5);
+    assert_eq!(universe.rule(), "B3/S23");
+
+    assert!(universe.set_rule("23/36").is_ok());
+    assert_eq!(universe.rule(), "B36/S23");
+
+    assert!(universe.set_rule("B9/S23").is_err());
+    assert_eq!(universe.rule(), "B36/S23");
+}
+
+#[wasm_bindgen_test]
+pub fn test_generations() {
+    let mut universe = Universe::new(5, 5);
+    assert!(universe.set_rule("B2/S/C3").is_ok());
+    assert_eq!(universe.states(), 3);
+
+    universe.set_cell(1, 2, 1).unwrap();
+    universe.set_cell(2, 2, 1).unwrap();
+    universe.tick();
+
+    assert_eq!(universe.get_cell(1, 2).unwrap(), 2);
+    assert_eq!(universe.get_cell(2, 2).unwrap(), 2);
+    assert_eq!(universe.get_cell(1, 1).unwrap(), 1);
+
+    universe.tick();
+    assert_eq!(universe.get_cell(1, 2).unwrap(), 0);
+}
+
+#[wasm_bindgen_test]
+pub fn test_hensel_rule() {
+    let mut universe = Universe::new(5, 5);
+
+    assert!(universe.set_rule("B3-cnqy/S234k").is_ok());
+    assert_eq!(universe.rule(), "B3-cnqy/S234k");
+
+    assert!(universe.set_rule("B2ceaikn/S12345678").is_ok());
+    assert_eq!(universe.rule(), "B2/S12345678");
+
+    assert!(universe.set_rule("B2x/S23").is_err());
+}
+
+#[wasm_bindgen_test]
+pub fn test_larger_than_life_rule() {
+    let mut universe = Universe::new(5, 5);
+
+    assert!(universe.set_rule("R5,C0,M1,S34..58,B34..45,NM").is_ok());
+    assert_eq!(universe.rule(), "R5,C0,M1,S34..58,B34..45,NM");
+
+    // Life as a Larger than Life rule
+    assert!(universe.set_rule("R1,C0,M0,S2..3,B3..3").is_ok());
+    universe.set_cell(1, 2, 1).unwrap();
+    universe.set_cell(2, 2, 1).unwrap();
+    universe.set_cell(3, 2, 1).unwrap();
+    universe.tick();
+
+    assert_eq!(universe.get_cell(2, 1).unwrap(), 1);
+    assert_eq!(universe.get_cell(2, 2).unwrap(), 1);
+    assert_eq!(universe.get_cell(2, 3).unwrap(), 1);
+    assert_eq!(universe.get_cell(1, 2).unwrap(), 0);
+}
+
+#[wasm_bindgen_test]
+pub fn test_neighbourhood_suffix() {
+    let mut universe = Universe::new(5, 5);
+
+    assert!(universe.set_rule("b2/s34h").is_ok());
+    assert_eq!(universe.rule(), "B2/S34H");
+
+    assert!(universe.set_rule("B1/SV").is_ok());
+    universe.set_cell(2, 2, 1).unwrap();
+    universe.tick();
+
+    assert_eq!(universe.get_cell(2, 1).unwrap(), 1);
+    assert_eq!(universe.get_cell(1, 1).unwrap(), 0);
+    assert_eq!(universe.get_cell(2, 2).unwrap(), 0);
+
+    assert!(universe.set_rule("B5/S3V").is_err());
+}
+
+#[wasm_bindgen_test]
+pub fn test_wireworld() {
+    let rle = "x = 4, y = 3, rule = WireWorld\nCBAC2$!";
+    let mut universe = Universe::from_rle(rle.into()).ok().unwrap();
+    assert_eq!(universe.rule(), "WireWorld");
+    assert_eq!(universe.states(), 4);
+
+    universe.tick();
+    assert_eq!(universe.to_rle(), "x = 4, y = 3, rule = WireWorld\n2CBA2$!");
+}
+
+#[wasm_bindgen_test]
+pub fn test_one_dimensional_rule() {
+    let mut universe = Universe::new(5, 3);
+
+    assert!(universe.set_rule("W30").is_ok());
+    assert!(universe.set_rule("W256").is_err());
+    assert!(universe.set_rule("T777,K3").is_ok());
+    assert_eq!(universe.rule(), "T777,K3,R1");
+
+    universe.set_rule("W90").ok();
+    universe.reset_row(false);
+    universe.tick();
+    universe.tick();
+    assert_eq!(universe.generation(), 2);
+    assert_eq!(universe.to_rle(), "x = 5, y = 3, rule = W90\n2bo$bobo$o3bo!");
+
+    universe.tick();
+    assert_eq!(universe.to_rle(), "x = 5, y = 3, rule = W90\nbobo$o3bo$2ob2o!");
+
+    // the rule continues from the last row which is not empty
+    let rle = "x = 5, y = 3, rule = W90\n2bo$bobo$o3bo!";
+    let mut universe = Universe::from_rle(rle.into()).ok().unwrap();
+    universe.tick();
+    assert_eq!(universe.to_rle(), "x = 5, y = 3, rule = W90\nbobo$o3bo$2ob2o!");
+
+    let mut universe = Universe::new(5, 3);
+    universe.tick();
+    universe.tick();
+    universe.set_cell(2, 0, 1).unwrap();
+    universe.set_rule("W90").ok();
+    universe.tick();
+    assert_eq!(universe.to_rle(), "x = 5, y = 3, rule = W90\n2bo$bobo$!");
+}
+
+#[wasm_bindgen_test]
+pub fn test_margolus_rule() {
+    let mut universe = Universe::new(6, 6);
+
+    assert!(universe.set_rule("MS,D15;14;13;3;11;5;6;1;7;9;10;2;12;4;8;0").is_ok());
+    assert_eq!(universe.rule(), "M15,14,13,3,11,5,6,1,7,9,10,2,12,4,8,0");
+    assert!(universe.set_rule("M15,1,2,3,4,5,6,7,8,9,10,11,12,13,14,0").is_ok());
+    assert!(universe.set_rule("M0,0,4,3,2,5,9,7,1,6,10,11,12,13,14,15").is_err());
+
+    universe.set_rule("M0,8,4,3,2,5,9,7,1,6,10,11,12,13,14,15").ok();
+    universe.set_cell(0, 0, 1).unwrap();
+    universe.tick();
+    universe.tick();
+
+    assert_eq!(universe.get_cell(2, 2).unwrap(), 1);
+    assert_eq!(universe.get_cell(0, 0).unwrap(), 0);
+
+    // blocks would overlap across the edges of a universe of odd size
+    assert!(universe.set_width(7).is_err());
+    assert!(universe.set_height(5).is_err());
+    assert!(universe.resize(7, 6, "center").is_err());
+    assert!(universe.set_topology("T6,7").is_err());
+    assert_eq!((universe.width(), universe.height()), (6, 6));
+    assert!(universe.set_width(8).is_ok());
+
+    let mut universe = Universe::new(5, 6);
+    assert!(universe.set_rule("M0,8,4,3,2,5,9,7,1,6,10,11,12,13,14,15").is_err());
+    let rle = "x = 5, y = 6, rule = M0,8,4,3,2,5,9,7,1,6,10,11,12,13,14,15:T5,6\n!";
+    assert!(Universe::from_rle(rle.into()).is_err());
+    let rle = "x = 5, y = 6, rule = M0,8,4,3,2,5,9,7,1,6,10,11,12,13,14,15\n!";
+    let universe = Universe::from_rle(rle.into()).ok().unwrap();
+    assert_eq!((universe.width(), universe.height()), (6, 6));
+}
+
+#[wasm_bindgen_test]
+pub fn test_map_rule() {
+    let life = "MAPARYXfhZofugWaH7oaIDogBZofuhogOiAaIDogIAAgAAWaH7oaIDogGiA6ICAAIAAaIDogIAAgACAAIAAAAAAAA";
+    let mut universe = Universe::new(5, 5);
+
+    assert_eq!(universe.rule_map(), Some(life.to_owned()));
+    assert!(universe.set_rule(life).is_ok());
+    assert_eq!(universe.rule(), "B3/S23");
+    assert!(universe.set_rule("MAPARYX").is_err());
+
+    // Life with births on a lone south-west neighbour
+    let rule = life.replacen("MAPARYX", "MAPCRYX", 1);
+    assert!(universe.set_rule(&rule).is_ok());
+    assert_eq!(universe.rule(), rule);
+
+    universe.set_cell(1, 1, 1).unwrap();
+    universe.tick();
+    assert_eq!(universe.get_cell(2, 0).unwrap(), 1);
+    assert_eq!(universe.get_cell(0, 2).unwrap(), 0);
+}
+
+#[wasm_bindgen_test]
+pub fn test_b0_rule() {
+    let mut universe = Universe::new(6, 6);
+    universe.set_rule("B0/S").ok();
+
+    universe.tick();
+    assert_eq!(universe.background(), 1);
+    assert_eq!(universe.to_rle(), "x = 6, y = 6, rule = B0/S\n6o$6o$6o$6o$6o$6o!");
+
+    universe.tick();
+    assert_eq!(universe.background(), 0);
+
+    universe.set_cell(2, 2, 1).unwrap();
+    universe.tick();
+    assert_eq!(universe.background(), 1);
+    let rle = universe.to_rle();
+    assert_eq!(rle, "x = 6, y = 6, rule = B0/S\n6o$o3b2o$o3b2o$o3b2o$6o$6o!");
+
+    // the actual states are read back, whatever the generation
+    let mut copy = Universe::from_rle(rle.as_str().into()).ok().unwrap();
+    copy.tick();
+    universe.tick();
+    assert_eq!(copy.to_rle(), universe.to_rle());
+}
+
+#[wasm_bindgen_test]
+pub fn test_custom_rule() {
+    let table = "@RULE Spread\n\
+        @TABLE\n\
+        n_states:3\n\
+        neighborhood:vonNeumann\n\
+        symmetries:rotate4\n\
+        var a={0,2}\n\
+        var b=a\n\
+        var c=a\n\
+        0,1,a,b,c,2\n\
+        @COLORS\n\
+        2 255 0 0\n";
+    let mut universe = Universe::new(3, 3);
+
+    assert!(universe.load_rule(table).is_ok());
+    assert_eq!(universe.rule(), "Spread");
+    assert_eq!(universe.states(), 3);
+
+    universe.set_cell(1, 1, 1).unwrap();
+    universe.tick();
+    assert_eq!(universe.to_rle(), "x = 3, y = 3, rule = Spread\n.B$BAB$.B!");
+
+    let universe = Universe::from_rle("x = 3, y = 1, rule = Spread\nA!".into());
+    assert!(universe.is_ok());
+
+    // a reflection swaps the west and east neighbours of one-dimensional
+    // rules, so that cells are born next to live cells on both sides
+    let grow = "@RULE Grow\n\
+        @TABLE\n\
+        n_states:2\n\
+        neighborhood:oneDimensional\n\
+        symmetries:reflect\n\
+        0,1,0,1\n";
+    let mut universe = Universe::new(5, 1);
+    assert!(universe.load_rule(grow).is_ok());
+
+    universe.set_cell(2, 0, 1).unwrap();
+    universe.tick();
+    assert_eq!(universe.get_cell(1, 0).unwrap(), 1);
+    assert_eq!(universe.get_cell(3, 0).unwrap(), 1);
+
+    // rules may be loaded again, but never hide a built-in rule
+    let mut universe = Universe::new(3, 3);
+    assert!(universe.load_rule(table).is_ok());
+    assert!(universe.load_rule(&table.replace("Spread", "B3/S23")).is_err());
+    assert!(universe.load_rule(&table.replace("Spread", "WireWorld")).is_err());
+    assert!(universe.set_rule("B3/S23").is_ok());
+    assert_eq!(universe.states(), 2);
+
+    let tree = "@RULE Down\n@TREE\nnum_states=2\nnum_neighbors=4\nnum_nodes=9\n\
+        1 0 0\n1 1 1\n2 0 0\n2 1 1\n3 2 2\n3 3 3\n4 4 4\n4 5 5\n5 6 7\n";
+    let mut universe = Universe::new(3, 3);
+    assert!(universe.load_rule(tree).is_ok());
+
+    universe.set_cell(1, 0, 1).unwrap();
+    universe.tick();
+    assert_eq!(universe.get_cell(1, 1).unwrap(), 1);
+    assert_eq!(universe.get_cell(1, 0).unwrap(), 0);
+}
+
+#[wasm_bindgen_test]
+pub fn test_species_rule() {
+    let mut universe = Universe::new(5, 5);
+
+    assert!(universe.set_rule("immigration").is_ok());
+    assert_eq!(universe.rule(), "Immigration");
+    assert_eq!(universe.states(), 3);
+    assert!(universe.set_rule("B36/S23QuadLife").is_ok());
+    assert_eq!(universe.rule(), "B36/S23QuadLife");
+    assert!(universe.set_rule("B3/S23/C3Immigration").is_err());
+
+    // blinker with 2 cells of species 1
+    universe.set_rule("Immigration").ok();
+    universe.set_cell(1, 2, 1).unwrap();
+    universe.set_cell(2, 2, 2).unwrap();
+    universe.set_cell(3, 2, 1).unwrap();
+    universe.tick();
+
+    assert_eq!(universe.get_cell(2, 1).unwrap(), 1);
+    assert_eq!(universe.get_cell(2, 2).unwrap(), 2);
+    assert_eq!(universe.get_cell(2, 3).unwrap(), 1);
+    assert_eq!(universe.populations(), vec![22, 2, 1]);
+
+    // blinker with 3 cells of distinct species
+    universe.clear();
+    universe.set_rule("QuadLife").ok();
+    universe.set_cell(1, 2, 1).unwrap();
+    universe.set_cell(2, 2, 2).unwrap();
+    universe.set_cell(3, 2, 3).unwrap();
+    universe.tick();
+
+    assert_eq!(universe.get_cell(2, 1).unwrap(), 4);
+    assert_eq!(universe.get_cell(2, 3).unwrap(), 4);
+    assert_eq!(universe.populations(), vec![22, 0, 1, 0, 2]);
+
+    // cells are counted with their actual state when the background is alive
+    let mut universe = Universe::new(4, 4);
+    universe.set_rule("B0/S").ok();
+    universe.set_cell(1, 1, 1).unwrap();
+    universe.tick();
+    assert_eq!(universe.background(), 1);
+    assert_eq!(universe.populations(), vec![9, 7]);
+}
+
+#[wasm_bindgen_test]
+pub fn test_reversible() {
+    let mut universe = Universe::new(8, 8);
+    universe.set_reversible(true);
+    assert!(universe.reversible());
+
+    for &(col, row) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2), (5, 5)].iter() {
+        universe.set_cell(col, row, 1).unwrap();
+    }
+    let cells = universe.get_cells().to_vec();
+
+    for _ in 0..7 {
+        universe.tick();
+    }
+    assert_ne!(universe.get_cells(), &cells[..]);
+
+    for _ in 0..7 {
+        universe.untick();
+    }
+    assert_eq!(universe.get_cells(), &cells[..]);
+    assert_eq!(universe.generation(), 0);
+
+    // with 3 states, next states are the rule states minus the previous ones
+    universe.clear();
+    universe.set_rule("B3/S23/C3").ok();
+    universe.set_cell(1, 1, 1).unwrap();
+    universe.tick();
+    assert_eq!(universe.get_cell(1, 1).unwrap(), 2);
+    universe.tick();
+    assert_eq!(universe.get_cell(1, 1).unwrap(), 2);
+    universe.untick();
+    universe.untick();
+    assert_eq!(universe.get_cell(1, 1).unwrap(), 1);
+
+    // rules with B0 are computed with the actual state of the cells
+    let mut universe = Universe::new(4, 4);
+    universe.set_rule("B0/S").ok();
+    universe.set_reversible(true);
+    universe.set_cell(1, 1, 1).unwrap();
+    let cells = universe.get_cells().to_vec();
+
+    universe.tick();
+    assert_eq!(universe.background(), 0);
+    assert_eq!(universe.populations(), vec![9, 7]);
+    for _ in 0..4 {
+        universe.tick();
+    }
+    for _ in 0..5 {
+        universe.untick();
+    }
+    assert_eq!(universe.get_cells(), &cells[..]);
+}
+
+#[wasm_bindgen_test]
+pub fn test_stochastic_rule() {
+    let mut universe = Universe::new(16, 16);
+
+    assert!(universe.set_rule("B3/S23@1,0.99").is_ok());
+    assert_eq!(universe.rule(), "B3/S23@1,0.99");
+    assert!(universe.set_rule("B3/S23@1,1.5").is_err());
+
+    // certain events do not draw random numbers
+    universe.set_rule("B3/S23@1,1").ok();
+    let state = universe.random_state();
+    universe.set_cell(1, 1, 1).unwrap();
+    universe.set_cell(1, 2, 1).unwrap();
+    universe.set_cell(1, 3, 1).unwrap();
+    universe.tick();
+    assert_eq!(universe.get_cell(0, 2).unwrap(), 1);
+    assert_eq!(universe.random_state(), state);
+
+    // new universes are seeded randomly
+    assert_ne!(Universe::new(4, 4).random_state(), Universe::new(4, 4).random_state());
+
+    // runs are reproduced from a seed or a saved state
+    universe.set_rule("B3/S23@0.5,0.5").ok();
+    universe.set_seed(42);
+    universe.randomize();
+    let state = universe.random_state();
+    universe.tick();
+    let cells = universe.get_cells().to_vec();
+
+    universe.set_seed(42);
+    universe.randomize();
+    assert!(universe.set_random_state(&state).is_ok());
+    universe.tick();
+    assert_eq!(universe.get_cells(), &cells[..]);
+
+    // cells failing to survive die, or decay with Generations rules
+    let block = |universe: &mut Universe, state| {
+        universe.clear();
+        for &(col, row) in [(1, 1), (2, 1), (1, 2), (2, 2)].iter() {
+            universe.set_cell(col, row, state).unwrap();
+        }
+        universe.tick();
+        universe.populations()
+    };
+    let mut universe = Universe::new(6, 6);
+
+    universe.set_rule("B3/S23/C3@1,0").ok();
+    assert_eq!(block(&mut universe, 1), vec![32, 0, 4]);
+    universe.set_rule("Immigration@1,0").ok();
+    assert_eq!(block(&mut universe, 1), vec![36, 0, 0]);
+    universe.set_rule("R1/T3/C3/NM@1,0").ok();
+    assert_eq!(block(&mut universe, 1), vec![36, 0, 0]);
+    universe.set_rule("WireWorld@1,0").ok();
+    assert_eq!(block(&mut universe, 3), vec![36, 0, 0, 0]);
+}
+
+#[wasm_bindgen_test]
+pub fn test_schedule() {
+    let mut universe = Universe::new(5, 5);
+
+    assert_eq!(universe.schedule(), "synchronous");
+    assert!(universe.set_schedule("alpha=0.5").is_ok());
+    assert_eq!(universe.schedule(), "alpha=0.5");
+    assert!(universe.set_schedule("alpha=0").is_err());
+    assert!(universe.set_schedule("spiral").is_err());
+
+    // a blinker dies when updated row by row
+    universe.set_schedule("sweep").ok();
+    universe.set_cell(2, 1, 1).unwrap();
+    universe.set_cell(2, 2, 1).unwrap();
+    universe.set_cell(2, 3, 1).unwrap();
+    universe.tick();
+    assert_eq!(universe.populations(), vec![25, 0]);
+
+    // updating all cells from the current generation is synchronous
+    universe.set_schedule("alpha=1").ok();
+    universe.set_cell(2, 1, 1).unwrap();
+    universe.set_cell(2, 2, 1).unwrap();
+    universe.set_cell(2, 3, 1).unwrap();
+    universe.tick();
+    assert_eq!(universe.get_cell(1, 2).unwrap(), 1);
+    assert_eq!(universe.get_cell(2, 2).unwrap(), 1);
+    assert_eq!(universe.get_cell(3, 2).unwrap(), 1);
+    assert_eq!(universe.populations(), vec![22, 3]);
+
+    // random orders are reproduced from a seed
+    universe.set_schedule("random-sequential").ok();
+    universe.set_seed(7);
+    universe.randomize();
+    universe.tick();
+    let cells = universe.get_cells().to_vec();
+
+    universe.set_seed(7);
+    universe.randomize();
+    universe.tick();
+    assert_eq!(universe.get_cells(), &cells[..]);
+}
+
+#[wasm_bindgen_test]
+pub fn test_cyclic_rule() {
+    let mut universe = Universe::new(5, 5);
+
+    assert!(universe.set_rule("R1/T3/C3/NM").is_ok());
+    assert_eq!(universe.rule(), "R1/T3/C3/NM");
+    assert_eq!(universe.states(), 3);
+    assert!(universe.set_rule("r2/t5/c4/nn/gh").is_ok());
+    assert_eq!(universe.rule(), "R2/T5/C4/NN/GH");
+    assert!(universe.set_rule("R1/T9/C3/NM").is_err());
+    assert!(universe.set_rule("R1/T1/C1").is_err());
+
+    // cells advance when enough neighbours are in the next state
+    universe.set_rule("R1/T2/C3").ok();
+    universe.set_cell(1, 1, 1).unwrap();
+    universe.set_cell(2, 1, 1).unwrap();
+    universe.set_cell(1, 2, 2).unwrap();
+    universe.tick();
+    assert_eq!(universe.get_cell(0, 0).unwrap(), 0);
+    assert_eq!(universe.get_cell(2, 2).unwrap(), 1);
+    assert_eq!(universe.get_cell(1, 1).unwrap(), 1);
+    assert_eq!(universe.get_cell(1, 2).unwrap(), 0);
+
+    // excited cells go through the refractory states back to rest
+    universe.clear();
+    universe.set_rule("R1/T1/C3/NN/GH").ok();
+    universe.set_cell(2, 2, 1).unwrap();
+    universe.tick();
+    assert_eq!(universe.get_cell(2, 2).unwrap(), 2);
+    assert_eq!(universe.get_cell(2, 1).unwrap(), 1);
+    assert_eq!(universe.get_cell(1, 1).unwrap(), 0);
+    universe.tick();
+    assert_eq!(universe.get_cell(2, 2).unwrap(), 0);
+    assert_eq!(universe.get_cell(2, 0).unwrap(), 1);
+}
+
+#[wasm_bindgen_test]
+pub fn test_turmites() {
+    let mut universe = Universe::new(5, 5);
+
+    assert_eq!(universe.turmite_rule(), "RL");
+    assert!(universe.set_turmite_rule("{{{1,2,0},{0,8,0}}}").is_ok());
+    assert_eq!(universe.turmite_rule(), "RL");
+    assert!(universe.set_turmite_rule("{{{1,2,1},{0,8,0}},{{1,2,1},{0,1,0}}}").is_ok());
+    assert_eq!(universe.turmite_rule(), "{{{1,2,1},{0,8,0}},{{1,2,1},{0,1,0}}}");
+    assert!(universe.set_turmite_rule("RX").is_err());
+    assert!(universe.set_turmite_rule("{{{1,3,0},{0,8,0}}}").is_err());
+    assert!(universe.set_turmite_rule("{{{1,2,2},{0,8,0}}}").is_err());
+
+    // Langton's ant turns right on dead cells and left on live cells
+    universe.set_turmite_rule("RL").ok();
+    universe.set_turmites_only(true);
+    universe.add_turmite(2, 2, 0).unwrap();
+    for _ in 0..5 {
+        universe.tick();
+    }
+    assert_eq!(universe.turmites(), vec![1, 2, 3, 0]);
+    assert_eq!(universe.get_cell(2, 2).unwrap(), 0);
+    assert_eq!(universe.get_cell(3, 2).unwrap(), 1);
+    assert_eq!(universe.get_cell(3, 3).unwrap(), 1);
+    assert_eq!(universe.get_cell(2, 3).unwrap(), 1);
+
+    // turmites are saved as RLE comments
+    assert!(universe.to_rle().starts_with("#T RL\n#A 1 2 3 0\nx = 5, y = 5"));
+
+    // turmites beyond the edges of a plane are refused
+    let rle = "#A 4 0 0 0\nx = 5, y = 5, rule = B3/S23:P5,5\n!";
+    assert!(Universe::from_rle(rle.into()).is_ok());
+    let rle = "#A 5 0 0 0\nx = 5, y = 5, rule = B3/S23:P5,5\n!";
+    assert!(Universe::from_rle(rle.into()).is_err());
+
+    universe.clear_turmites();
+    assert!(universe.turmites().is_empty());
+}
+
+#[wasm_bindgen_test]
+pub fn test_zones() {
+    let mut universe = Universe::new(8, 5);
+
+    assert!(universe.add_zone("frozen").is_ok());
+    assert!(universe.add_zone("B36/S23").is_ok());
+    assert!(universe.add_zone("B3/S2X").is_err());
+    assert_eq!(universe.zone_rule(0), Some(String::from("B3/S23")));
+    assert_eq!(universe.zone_rule(1), Some(String::from("frozen")));
+    assert_eq!(universe.zone_rule(2), Some(String::from("B36/S23")));
+    assert_eq!(universe.zone_rule(3), None);
+    assert!(universe.set_zone_rule(0, "B36/S23").is_err());
+    assert!(universe.paint_zone(0, 0, 3, 5, 3).is_err());
+
+    // cells of a frozen zone never change
+    assert!(universe.paint_zone(0, 0, 3, 5, 1).is_ok());
+    assert_eq!(universe.get_zone(2, 4).unwrap(), 1);
+    assert_eq!(universe.get_zone(3, 4).unwrap(), 0);
+    universe.set_cell(1, 2, 1).unwrap();
+    universe.set_cell(5, 1, 1).unwrap();
+    universe.set_cell(5, 2, 1).unwrap();
+    universe.set_cell(5, 3, 1).unwrap();
+    universe.tick();
+    assert_eq!(universe.get_cell(1, 2).unwrap(), 1);
+    assert_eq!(universe.get_cell(4, 2).unwrap(), 1);
+    assert_eq!(universe.get_cell(5, 1).unwrap(), 0);
+
+    // each zone follows its own rule
+    universe.clear();
+    universe.clear_zones();
+    universe.add_zone("B36/S23").ok();
+    universe.paint_zone(4, 0, 4, 5, 1).ok();
+    for &col in &[0, 1, 2, 4, 5, 6] {
+        universe.set_cell(col, 1, 1).unwrap();
+        universe.set_cell(col, 3, 1).unwrap();
+    }
+    universe.tick();
+    assert_eq!(universe.get_cell(1, 2).unwrap(), 0);
+    assert_eq!(universe.get_cell(5, 2).unwrap(), 1);
+
+    // zones are numbered up to 255
+    universe.clear_zones();
+    for zone in 1..=255 {
+        assert_eq!(universe.add_zone("frozen").ok(), Some(zone));
+    }
+    assert!(universe.add_zone("frozen").is_err());
+
+    // and removed from unbounded universes
+    assert!(universe.set_topology("unbounded").is_ok());
+    assert_eq!(universe.zone_rule(1), None);
+}
+
+#[wasm_bindgen_test]
+pub fn test_alternating_rule() {
+    let mut universe = Universe::new(5, 5);
+
+    assert!(universe.set_rule("B3/S23-B36/S125").is_ok());
+    assert_eq!(universe.rule(), "B3/S23-B36/S125");
+    assert!(universe.to_rle().starts_with("x = 5, y = 5, rule = B3/S23-B36/S125"));
+    assert!(universe.set_rule("B2-a/S12-B3/S23-B2-a/S1").is_ok());
+    assert_eq!(universe.rule(), "B2-a/S12-B3/S23-B2-a/S1");
+    assert!(universe.set_rule("B2-a/S12").is_ok());
+    assert_eq!(universe.rule(), "B2-a/S12");
+    assert!(universe.set_rule("B3/S23-").is_err());
+
+    // rules are applied in turn by generation
+    universe.set_rule("B3/S23-B/S").ok();
+    universe.set_cell(2, 1, 1).unwrap();
+    universe.set_cell(2, 2, 1).unwrap();
+    universe.set_cell(2, 3, 1).unwrap();
+    universe.tick();
+    assert_eq!(universe.populations(), vec![22, 3]);
+
+    // and RLE files keep the generation, hence the next rule
+    let rle = universe.to_rle();
+    assert!(rle.starts_with("#G 1\nx = 5, y = 5, rule = B3/S23-B/S"));
+    let mut copy = Universe::from_rle(rle.as_str().into()).ok().unwrap();
+    assert_eq!(copy.generation(), 1);
+    copy.tick();
+    assert_eq!(copy.populations(), vec![25, 0]);
+
+    universe.tick();
+    assert_eq!(universe.populations(), vec![25, 0]);
+}
+
+#[wasm_bindgen_test]
+pub fn test_rule_notations() {
+    for rule in &["23/3", "B3/S23", "b3s23", "S23B3", "C224"] {
+        assert_eq!(Universe::canonical_rule(rule).ok(), Some(String::from("B3/S23")));
+    }
+    assert!(Universe::canonical_rule("B9/S23").is_err());
+
+    // Wolfram codes need their prefix, digits alone not being a rule
+    assert!(Universe::canonical_rule("3").is_err());
+    assert!(Universe::canonical_rule("224").is_err());
+
+    assert_eq!(
+        Universe::convert_rule("B3/S23", "S/B").ok(),
+        Some(Some(String::from("23/3")))
+    );
+    assert_eq!(
+        Universe::convert_rule("B3/S23", "wolfram").ok(),
+        Some(Some(String::from("C224")))
+    );
+    assert_eq!(
+        Universe::convert_rule("345/2/4", "B/S").ok(),
+        Some(Some(String::from("B2/S345/C4")))
+    );
+    assert_eq!(Universe::convert_rule("B2-a/S12", "S/B").ok(), Some(None));
+    assert_eq!(Universe::convert_rule("WireWorld", "MAP").ok(), Some(None));
+    assert!(Universe::convert_rule("B3/S23", "Hensel").is_err());
+
+    assert_eq!(
+        Universe::rule_notations("B3/S23").ok(),
+        Some(vec![
+            String::from("B/S"),
+            String::from("S/B"),
+            String::from("Wolfram"),
+            String::from("MAP"),
+        ])
+    );
+    assert_eq!(
+        Universe::rule_notations("B2-a/S12").ok(),
+        Some(vec![String::from("B/S"), String::from("MAP")])
+    );
+}
+
+#[wasm_bindgen_test]
+pub fn test_topology() {
+    let mut universe = Universe::new(5, 5);
+
+    assert_eq!(universe.topology(), "T5,5");
+    assert!(universe.set_topology("klein").is_err());
+
+    // a blinker on the top edge wraps around on a torus
+    for col in 1..=3 {
+        universe.set_cell(col, 0, 1).unwrap();
+    }
+    universe.tick();
+    assert_eq!(universe.get_cell(2, 4).unwrap(), 1);
+    assert_eq!(universe.get_cell(2, -1).unwrap(), 1);
+
+    // but only sees dead cells beyond the edge of a plane
+    universe.clear();
+    assert!(universe.set_topology("plane").is_ok());
+    assert_eq!(universe.topology(), "P5,5");
+    for col in 1..=3 {
+        universe.set_cell(col, 0, 1).unwrap();
+    }
+    universe.tick();
+    assert_eq!(universe.populations(), vec![23, 2]);
+    assert_eq!(universe.get_cell(2, 0).unwrap(), 1);
+    assert_eq!(universe.get_cell(2, 1).unwrap(), 1);
+    assert_eq!(universe.get_cell(2, 4).unwrap(), 0);
+
+    // cells beyond the edges cannot be accessed
+    assert!(universe.get_cell(2, -1).is_err());
+    assert!(universe.set_cell(5, 0, 1).is_err());
+    assert!(universe.toggle_cell(0, 5).is_err());
+    assert!(universe.get_zone(-1, 0).is_err());
+    assert!(universe.add_turmite(-1, 0, 0).is_err());
+    assert!(universe.turmites().is_empty());
+
+    // and cells translated beyond the edges are dropped
+    universe.translate(0, -1);
+    assert_eq!(universe.populations(), vec![24, 1]);
+    assert_eq!(universe.get_cell(2, 0).unwrap(), 1);
+    universe.translate(0, 1);
+    assert_eq!(universe.get_cell(2, 1).unwrap(), 1);
+    assert_eq!(universe.get_cell(2, 0).unwrap(), 0);
+}
+
+#[wasm_bindgen_test]
+pub fn test_topology_grids() {
+    let mut universe = Universe::new(5, 5);
+
+    for grid in &["T0,5", "K5,5", "K5*,5*", "T5+1,5+1", "P5*,5", "S5,4", "X5,5"] {
+        assert!(universe.set_topology(grid).is_err());
+    }
+
+    // cells crossing the bottom edge of a shifted torus move right
+    assert!(universe.set_topology("T6+1,4").is_ok());
+    assert_eq!(universe.topology(), "T6+1,4");
+    assert_eq!((universe.width(), universe.height()), (6, 4));
+    universe.set_cell(1, 0, 1).unwrap();
+    assert_eq!(universe.get_cell(0, 4).unwrap(), 1);
+    assert_eq!(universe.get_cell(5, 4).unwrap(), 0);
+
+    // columns are reversed when crossing the twisted edges of a Klein bottle
+    universe.clear();
+    assert!(universe.set_topology("K6*,4").is_ok());
+    universe.set_cell(5, 3, 1).unwrap();
+    assert_eq!(universe.get_cell(0, -1).unwrap(), 1);
+    assert_eq!(universe.get_cell(-1, 3).unwrap(), 1);
+
+    // and rows too on a cross-surface
+    assert!(universe.set_topology("C6,4").is_ok());
+    assert_eq!(universe.get_cell(0, -1).unwrap(), 1);
+    assert_eq!(universe.get_cell(-1, 0).unwrap(), 1);
+
+    // the top edge of a sphere is its left edge
+    universe.clear();
+    assert!(universe.set_topology("S5").is_ok());
+    assert_eq!(universe.topology(), "S5");
+    universe.set_cell(0, 2, 1).unwrap();
+    assert_eq!(universe.get_cell(2, -1).unwrap(), 1);
+    assert!(universe.get_cell(-1, -1).is_err());
+    assert!(universe.to_rle().starts_with("x = 5, y = 5, rule = B3/S23:S5\n"));
+
+    // grids are read and written in the rule of RLE files
+    let rle = "x = 6, y = 4, rule = B3/S23:K6,4*\n3o3$!";
+    let universe = Universe::from_rle(rle.into()).ok().unwrap();
+    assert_eq!(universe.topology(), "K6,4*");
+    assert_eq!(universe.to_rle(), rle);
+    let universe = Universe::from_rle("x = 3, y = 1, rule = B3/S23:P8,2\n3o!".into());
+    assert_eq!(universe.ok().map(|universe| universe.topology()), Some(String::from("P8,2")));
+    assert!(Universe::from_rle("x = 3, y = 1, rule = B3/S23:Q8\n3o!".into()).is_err());
+}
+
+#[wasm_bindgen_test]
+pub fn test_unbounded() {
+    let mut universe = Universe::new(8, 8);
+
+    assert!(universe.set_topology("P0,0").is_ok());
+    assert_eq!(universe.topology(), "P0,0");
+
+    // a glider flies away without hitting any edge
+    for &(col, row) in &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+        universe.set_cell(col, row, 1).unwrap();
+    }
+    for _ in 0..80 {
+        universe.tick();
+    }
+    assert_eq!(universe.populations()[1], 5);
+    for &(col, row) in &[(21, 20), (22, 21), (20, 22), (21, 22), (22, 22)] {
+        assert_eq!(universe.get_cell(col, row).unwrap(), 1);
+    }
+
+    // the frame follows it, made of chunks of 16 by 16 cells
+    assert_eq!(universe.origin(), vec![16, 16]);
+    assert_eq!((universe.width(), universe.height()), (16, 16));
+    assert_eq!(universe.get_cell(1000, -1000).unwrap(), 0);
+
+    // grows to include the cells set beyond it, and shrinks back
+    universe.set_cell(-40, 20, 1).unwrap();
+    assert_eq!(universe.origin(), vec![-48, 16]);
+    assert_eq!(universe.get_cell(-40, 20).unwrap(), 1);
+    universe.tick();
+    universe.tick();
+    assert_eq!(universe.origin(), vec![16, 16]);
+    assert_eq!(universe.populations()[1], 5);
+
+    // translating moves the origin
+    universe.translate(-20, -20);
+    assert_eq!(universe.origin(), vec![-4, -4]);
+
+    // and the viewport selects the rendered cells
+    universe.set_viewport(0, 0, 4, 4);
+    assert_eq!(universe.viewport(), vec![0, 0, 4, 4]);
+    assert_eq!(universe.render_to_string(), "◻◻◻◻\n◻◻◼◻\n◼◻◼◻\n◻◼◼◻\n");
+    universe.clear_viewport();
+    assert_eq!(universe.viewport(), vec![-4, -4, 16, 16]);
+    let rle = universe.to_rle();
+    assert!(rle.starts_with("#R -4 -4\nx = 16, y = 16, rule = B3/S23:P0,0\n"));
+
+    // and is read back
+    let copy = Universe::from_rle(rle.as_str().into()).ok().unwrap();
+    assert_eq!(copy.origin(), vec![-4, -4]);
+    assert_eq!(copy.get_cell(1, 2).unwrap(), universe.get_cell(1, 2).unwrap());
+    assert_eq!(copy.to_rle(), rle);
+
+    // turmites keep their coordinates as the frame moves
+    assert!(universe.add_turmite(-30, 0, 1).is_ok());
+    assert_eq!(universe.origin(), vec![-32, -4]);
+    assert_eq!(universe.turmites(), vec![-30, 0, 1, 0]);
+    universe.tick();
+    assert_eq!(universe.turmites(), vec![-30, 1, 2, 0]);
+
+    // but unbounded universes have no zones
+    assert!(universe.add_zone("B36/S23").is_err());
+    assert!(universe.paint_zone(0, 0, 4, 4, 0).is_err());
+    assert_eq!(universe.get_zone(1000, 1000).unwrap(), 0);
+}
+
+#[wasm_bindgen_test]
+pub fn test_resize() {
+    let mut universe = Universe::new(5, 5);
+
+    for &(col, row) in &[(1, 2), (2, 2), (3, 2), (4, 4)] {
+        universe.set_cell(col, row, 1).unwrap();
+    }
+    let cells = universe.get_cells().to_vec();
+
+    // the anchor stays in place
+    assert_eq!(universe.resize(7, 7, "center").ok(), Some(vec![]));
+    assert_eq!((universe.width(), universe.height()), (7, 7));
+    assert_eq!(universe.get_cell(2, 3).unwrap(), 1);
+    assert_eq!(universe.get_cell(5, 5).unwrap(), 1);
+    assert_eq!(universe.resize(5, 5, "center").ok(), Some(vec![]));
+    assert_eq!(universe.get_cells(), &cells[..]);
+    assert!(universe.resize(5, 5, "middle").is_err());
+
+    // the removed cells are returned to undo the resize
+    let removed = universe.resize(3, 3, "top-left").ok().unwrap();
+    assert_eq!(removed, vec![3, 2, 1, 4, 4, 1]);
+    assert_eq!(universe.populations()[1], 2);
+    assert_eq!(universe.resize(5, 5, "top-left").ok(), Some(vec![]));
+    for triple in removed.chunks(3) {
+        universe.set_cell(triple[0], triple[1], triple[2] as u8).unwrap();
+    }
+    assert_eq!(universe.get_cells(), &cells[..]);
+
+    // the universe fits its pattern
+    assert_eq!(universe.fit_to_pattern(1), vec![0, -1]);
+    assert_eq!((universe.width(), universe.height()), (6, 5));
+    assert_eq!(universe.get_cell(1, 1).unwrap(), 1);
+    universe.set_cell(4, 3, 0).unwrap();
+    assert_eq!(universe.fit_to_pattern(0), vec![-1, -1]);
+    assert_eq!((universe.width(), universe.height()), (3, 1));
+
+    // or grows to contain a rectangle
+    assert_eq!(universe.grow_to_contain(-2, 0, 1, 2), vec![2, 0]);
+    assert_eq!((universe.width(), universe.height()), (5, 2));
+    assert_eq!(universe.get_cell(2, 0).unwrap(), 1);
+    assert_eq!(universe.grow_to_contain(0, 0, 2, 2), vec![0, 0]);
+    assert_eq!((universe.width(), universe.height()), (5, 2));
+}
+
+#[wasm_bindgen_test]
+pub fn test_packed_generation() {
+    // a spaceship crosses the words of packed rows and wraps around
+    let mut universe = Universe::new(130, 7);
+    for &(col, row) in &[(1, 1), (4, 1), (0, 2), (0, 3), (4, 3), (0, 4), (1, 4), (2, 4), (3, 4)] {
+        universe.set_cell(col + 62, row, 1).unwrap();
+    }
+    let cells = universe.get_cells().to_vec();
+    for _ in 0..130 {
+        universe.tick();
+    }
+    assert_eq!(universe.populations()[1], 9);
+    assert_ne!(universe.get_cells(), &cells[..]);
+    for _ in 0..130 {
+        universe.tick();
+    }
+    assert_eq!(universe.get_cells(), &cells[..]);
+
+    // cells beyond the edges of a plane are dead
+    let mut universe = Universe::new(5, 5);
+    for row in 1..4 {
+        universe.set_cell(0, row, 1).unwrap();
+    }
+    assert!(universe.set_topology("plane").is_ok());
+    universe.tick();
+    assert_eq!(universe.get_cell(0, 2).unwrap(), 1);
+    assert_eq!(universe.get_cell(1, 2).unwrap(), 1);
+    assert_eq!(universe.populations()[1], 2);
+
+    // cells changed between two generations are not packed from before
+    let mut universe = Universe::new(5, 5);
+    for col in 1..4 {
+        universe.set_cell(col, 2, 1).unwrap();
+    }
+    universe.tick();
+    universe.set_cell(2, 1, 0).unwrap();
+    universe.tick();
+    assert_eq!(universe.populations()[1], 0);
+
+    // packed cells match the cells, rows starting at a new word
+    let mut universe = Universe::new(70, 3);
+    universe.set_cell(65, 1, 1).unwrap();
+    universe.set_cell(66, 1, 1).unwrap();
+    universe.set_cell(67, 1, 1).unwrap();
+    universe.tick();
+    assert_eq!(universe.packed_len(), 6);
+    let words = unsafe { std::slice::from_raw_parts(universe.packed_cells(), 6) };
+    assert_eq!(words, &[0, 1 << 2, 0, 1 << 2, 0, 1 << 2]);
+    assert_eq!(universe.get_cell(66, 0).unwrap(), 1);
+    assert_eq!(universe.get_cell(66, 2).unwrap(), 1);
+    assert!(universe.set_rule("B2/S/C3").is_ok());
+    assert!(!universe.packed_cells().is_null());
+    universe.set_cell(0, 0, 2).unwrap();
+    assert!(universe.packed_cells().is_null());
+    assert_eq!(universe.packed_len(), 0);
+}
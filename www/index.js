@@ -1,5 +1,4 @@
 import {
-    Cell,
     Universe,
 } from "wasm-game-of-life";
 
@@ -8,6 +7,7 @@ const CELL_SIZE = 10; // px
 const GRID_COLOR = "#CCCCCC";
 const DEAD_COLOR = "#FFFFFF";
 const ALIVE_COLOR = "#000000";
+const ALIVE_CELL = 1;
 
 const playPauseButton = document.querySelector("#play-pause");
 const stepButton = document.querySelector("#step");
@@ -43,6 +43,7 @@ function throttle(fn, wait) {
 function createController(tickCount) {
     let universe = Universe.new(32, 32);
 
+    universe.set_seed(BigInt(Date.now()));
     universe.randomize();
 
     const canvas = document.getElementById("game-of-life-canvas");
@@ -161,7 +162,7 @@ function createController(tickCount) {
                 [col    , row + 1, ],
                 [col + 1, row    , ],
                 [col + 1, row + 1, ],
-            ], Cell.Alive)
+            ], ALIVE_CELL)
         } else if (event.shiftKey) {
             universe.set_cells([
                 [col - 1, row - 2, ], [col - 1, row - 3, ], [col - 1, row - 4, ],
@@ -184,7 +185,7 @@ function createController(tickCount) {
                 [col + 3, row + 1, ], [col + 3, row + 6, ],
                 [col + 4, row + 1, ], [col + 4, row + 6, ],
                 [col + 6, row + 2, ], [col + 6, row + 3, ], [col + 6, row + 4, ],
-            ], Cell.Alive)
+            ], ALIVE_CELL)
 
         } else {
             universe.toggle_cell(col, row);
@@ -242,13 +243,23 @@ function createController(tickCount) {
         },
         setWidth(w) {
             if (!running) {
-                universe.set_width(w);
+                try {
+                    universe.set_width(w);
+                } catch (err) {
+                    // Margolus rules need an even size
+                    sizeWidthInput.value = universe.width();
+                }
                 render();
             }
         },
         setHeight(h) {
             if (!running) {
-                universe.set_height(h);
+                try {
+                    universe.set_height(h);
+                } catch (err) {
+                    // Margolus rules need an even size
+                    sizeHeightInput.value = universe.height();
+                }
                 render();
             }
         },